@@ -0,0 +1,25 @@
+-- Adds Glicko-2 rating deviation and volatility to user_stats.
+-- Existing ratings are kept as they are since Glicko-2 uses the same scale as Elo.
+-- Deviation is seeded from the number of games played so established
+-- players start with a more certain rating than newcomers.
+
+ALTER TABLE public.user_stats ADD COLUMN IF NOT EXISTS deviation float8 NOT NULL DEFAULT 350;
+ALTER TABLE public.user_stats ADD COLUMN IF NOT EXISTS volatility float8 NOT NULL DEFAULT 0.06;
+ALTER TABLE public.user_stats ADD COLUMN IF NOT EXISTS last_game timestamptz NULL;
+
+UPDATE public.user_stats
+SET deviation = GREATEST(60, 350 - 10 * (wins + losses + draws)),
+	volatility = 0.06;
+
+UPDATE public.user_stats s
+SET last_game = g.last_game
+FROM (
+	SELECT user_id, MAX(end_time) AS last_game
+	FROM (
+		SELECT user1_id AS user_id, end_time FROM public.games
+		UNION ALL
+		SELECT user2_id AS user_id, end_time FROM public.games
+	) t
+	GROUP BY user_id
+) g
+WHERE s.user_id = g.user_id;
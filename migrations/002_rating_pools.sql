@@ -0,0 +1,31 @@
+-- Moves ratings from user_stats into per pool user_ratings table.
+-- Existing ratings become ratings of the default pool (classic, blitz)
+-- since every game played so far used its 60 second time control.
+-- user_stats keeps overall wins, losses and draws across all pools.
+
+CREATE TABLE IF NOT EXISTS public.user_ratings (
+	user_id int8 NOT NULL,
+	variant varchar NOT NULL,
+	time_control varchar NOT NULL,
+	wins int8 NOT NULL DEFAULT 0,
+	losses int8 NOT NULL DEFAULT 0,
+	draws int8 NOT NULL DEFAULT 0,
+	elo int8 NOT NULL DEFAULT 1200,
+	deviation float8 NOT NULL DEFAULT 350,
+	volatility float8 NOT NULL DEFAULT 0.06,
+	last_game timestamptz NULL,
+	CONSTRAINT user_ratings_pk PRIMARY KEY (user_id, variant, time_control)
+);
+
+INSERT INTO public.user_ratings (user_id, variant, time_control, wins, losses, draws, elo, deviation, volatility, last_game)
+SELECT user_id, 'classic', 'blitz', wins, losses, draws, elo, deviation, volatility, last_game
+FROM public.user_stats
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE public.user_stats DROP COLUMN IF EXISTS elo;
+ALTER TABLE public.user_stats DROP COLUMN IF EXISTS deviation;
+ALTER TABLE public.user_stats DROP COLUMN IF EXISTS volatility;
+ALTER TABLE public.user_stats DROP COLUMN IF EXISTS last_game;
+
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS variant varchar NOT NULL DEFAULT 'classic';
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS time_control varchar NOT NULL DEFAULT 'blitz';
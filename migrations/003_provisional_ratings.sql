@@ -0,0 +1,14 @@
+-- Tracks number of games played in each rating pool
+-- and records how every rating change was calculated.
+
+ALTER TABLE public.user_ratings ADD COLUMN IF NOT EXISTS games_played int8 NOT NULL DEFAULT 0;
+
+UPDATE public.user_ratings SET games_played = wins + losses + draws;
+
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS rating_system varchar NOT NULL DEFAULT 'elo';
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user1_deviation float8 NOT NULL DEFAULT 350;
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user2_deviation float8 NOT NULL DEFAULT 350;
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user1_k_factor float8 NULL;
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user2_k_factor float8 NULL;
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user1_provisional bool NOT NULL DEFAULT false;
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user2_provisional bool NOT NULL DEFAULT false;
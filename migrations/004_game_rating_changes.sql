@@ -0,0 +1,7 @@
+-- Records post-game ratings and rating changes of both players.
+-- Games recorded before this migration have no rating data and keep zeros.
+
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user1_elo_after int8 NOT NULL DEFAULT 0;
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user2_elo_after int8 NOT NULL DEFAULT 0;
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user1_elo_delta int8 NOT NULL DEFAULT 0;
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS user2_elo_delta int8 NOT NULL DEFAULT 0;
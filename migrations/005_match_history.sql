@@ -0,0 +1,6 @@
+-- Marks games as rated or unrated and speeds up match history queries.
+
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS rated bool NOT NULL DEFAULT true;
+
+CREATE INDEX IF NOT EXISTS games_user1_history_idx ON public.games (user1_id, end_time DESC, game_id DESC);
+CREATE INDEX IF NOT EXISTS games_user2_history_idx ON public.games (user2_id, end_time DESC, game_id DESC);
@@ -0,0 +1,6 @@
+-- Records moves and the way every game ended.
+-- Moves are stored as a string of played field indices (0-8) in order they were played.
+-- Games recorded before this migration have no moves and no end reason.
+
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS moves varchar NOT NULL DEFAULT '';
+ALTER TABLE public.games ADD COLUMN IF NOT EXISTS end_reason varchar NULL;
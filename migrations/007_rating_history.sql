@@ -0,0 +1,27 @@
+-- Keeps a rating history row for every rating change.
+-- History is backfilled from rated games recorded before this migration.
+
+CREATE TABLE IF NOT EXISTS public.rating_history (
+	id bigserial NOT NULL,
+	user_id int8 NOT NULL,
+	game_id uuid NOT NULL,
+	variant varchar NOT NULL,
+	time_control varchar NOT NULL,
+	elo_before int8 NOT NULL,
+	elo_after int8 NOT NULL,
+	recorded_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT rating_history_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS rating_history_user_idx ON public.rating_history (user_id, variant, time_control, recorded_at);
+
+INSERT INTO public.rating_history (user_id, game_id, variant, time_control, elo_before, elo_after, recorded_at)
+SELECT user_id, game_id, variant, time_control, elo_before, elo_after, end_time FROM (
+	SELECT user1_id AS user_id, game_id, variant, time_control, user1_elo AS elo_before, user1_elo_after AS elo_after, end_time
+	FROM public.games WHERE rated AND user1_elo_after <> 0
+	UNION ALL
+	SELECT user2_id AS user_id, game_id, variant, time_control, user2_elo AS elo_before, user2_elo_after AS elo_after, end_time
+	FROM public.games WHERE rated AND user2_elo_after <> 0
+) g
+WHERE NOT EXISTS (SELECT 1 FROM public.rating_history h WHERE h.game_id = g.game_id AND h.user_id = g.user_id)
+ORDER BY end_time;
@@ -0,0 +1,12 @@
+-- Friend requests and friendships between users.
+
+CREATE TABLE IF NOT EXISTS public.friendships (
+	requester_id int8 NOT NULL,
+	addressee_id int8 NOT NULL,
+	accepted bool NOT NULL DEFAULT false,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	accepted_on timestamptz NULL,
+	CONSTRAINT friendships_pk PRIMARY KEY (requester_id, addressee_id)
+);
+
+CREATE INDEX IF NOT EXISTS friendships_addressee_idx ON public.friendships (addressee_id);
@@ -0,0 +1,24 @@
+-- Direct and in-game chat messages and per user mutes.
+
+CREATE TABLE IF NOT EXISTS public.messages (
+	id bigserial NOT NULL,
+	sender_id int8 NOT NULL,
+	recipient_id int8 NOT NULL,
+	game_id uuid NULL,
+	body varchar NOT NULL,
+	sent_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	reported_by int8 NULL,
+	reported_at timestamptz NULL,
+	removed bool NOT NULL DEFAULT false,
+	CONSTRAINT messages_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS messages_conversation_idx ON public.messages (LEAST(sender_id, recipient_id), GREATEST(sender_id, recipient_id), id DESC);
+CREATE INDEX IF NOT EXISTS messages_reported_idx ON public.messages (reported_at) WHERE reported_by IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS public.user_mutes (
+	user_id int8 NOT NULL,
+	muted_id int8 NOT NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT user_mutes_pk PRIMARY KEY (user_id, muted_id)
+);
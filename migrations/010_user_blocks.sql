@@ -0,0 +1,10 @@
+-- Users blocked by other users.
+
+CREATE TABLE IF NOT EXISTS public.user_blocks (
+	user_id int8 NOT NULL,
+	blocked_id int8 NOT NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT user_blocks_pk PRIMARY KEY (user_id, blocked_id)
+);
+
+CREATE INDEX IF NOT EXISTS user_blocks_blocked_idx ON public.user_blocks (blocked_id);
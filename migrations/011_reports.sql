@@ -0,0 +1,19 @@
+-- Player reports reviewed by moderators.
+
+CREATE TABLE IF NOT EXISTS public.reports (
+	id bigserial NOT NULL,
+	reporter_id int8 NOT NULL,
+	reported_id int8 NOT NULL,
+	game_id uuid NULL,
+	category varchar NOT NULL,
+	description varchar NOT NULL DEFAULT '',
+	status varchar NOT NULL DEFAULT 'open',
+	assignee_id int8 NULL,
+	resolution varchar NULL,
+	banned bool NOT NULL DEFAULT false,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	resolved_on timestamptz NULL,
+	CONSTRAINT reports_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS reports_status_idx ON public.reports (status, created_on);
@@ -0,0 +1,3 @@
+-- Stores the reason user was banned for.
+
+ALTER TABLE public.users ADD COLUMN IF NOT EXISTS ban_reason varchar NULL;
@@ -0,0 +1,29 @@
+-- Append-only log of admin actions.
+
+CREATE TABLE IF NOT EXISTS public.audit_log (
+	id bigserial NOT NULL,
+	admin_id int8 NOT NULL,
+	"action" varchar NOT NULL,
+	target_user_id int8 NULL,
+	target_game_id uuid NULL,
+	target_report_id int8 NULL,
+	before_value varchar NULL,
+	after_value varchar NULL,
+	reason varchar NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT audit_log_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_admin_idx ON public.audit_log (admin_id, id);
+CREATE INDEX IF NOT EXISTS audit_log_target_user_idx ON public.audit_log (target_user_id, id);
+
+-- Audit log is append-only
+CREATE OR REPLACE FUNCTION public.audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+	RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_log_append_only ON public.audit_log;
+CREATE TRIGGER audit_log_append_only BEFORE UPDATE OR DELETE ON public.audit_log
+	FOR EACH ROW EXECUTE FUNCTION public.audit_log_append_only();
@@ -0,0 +1,10 @@
+-- Single-use tokens for resetting forgotten passwords.
+
+CREATE TABLE IF NOT EXISTS public.password_reset (
+	id uuid NOT NULL,
+	user_id int8 NOT NULL,
+	time_generated timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	expires_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0) + '01:00:00'::interval,
+	CONSTRAINT password_reset_pk PRIMARY KEY (id),
+	CONSTRAINT password_reset_unique UNIQUE (user_id)
+);
@@ -0,0 +1,3 @@
+-- Deleted users are anonymised instead of removed so game history of their opponents is kept.
+
+ALTER TABLE public.users ADD COLUMN IF NOT EXISTS deleted_on timestamptz NULL;
@@ -0,0 +1,11 @@
+-- Links accounts of external OpenID Connect providers to users.
+-- A user can have at most one identity per provider.
+
+CREATE TABLE IF NOT EXISTS public.user_identities (
+	provider varchar NOT NULL,
+	subject varchar NOT NULL,
+	user_id int8 NOT NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT user_identities_pk PRIMARY KEY (provider, subject),
+	CONSTRAINT user_identities_unique_user UNIQUE (user_id, provider)
+);
@@ -0,0 +1,21 @@
+-- TOTP two-factor authentication. Secret is unconfirmed until the first code is entered.
+
+CREATE TABLE IF NOT EXISTS public.user_totp (
+	user_id int8 NOT NULL,
+	secret bytea NOT NULL,
+	last_step int8 NULL,
+	confirmed_on timestamptz NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT user_totp_pk PRIMARY KEY (user_id)
+);
+
+-- One-time codes used when the authenticator is lost.
+
+CREATE TABLE IF NOT EXISTS public.recovery_codes (
+	id bigserial NOT NULL,
+	user_id int8 NOT NULL,
+	code_hash varchar NOT NULL,
+	used_on timestamptz NULL,
+	CONSTRAINT recovery_codes_pk PRIMARY KEY (id)
+);
+CREATE INDEX IF NOT EXISTS recovery_codes_user_idx ON public.recovery_codes (user_id);
@@ -10,9 +10,10 @@ use ttt_db::TttDbConn;
 mod routes;
 mod util;
 use ttt_game_server::server::GameServer;
+use ttt_lobby::LobbyServer;
 use ttt_mailer::MailWorker;
 use ttt_matchmaking::MatchmakingWorker;
-use util::env;
+use util::{env, SessionGuard};
 
 use routes::*;
 
@@ -22,6 +23,7 @@ struct AppState {
     mm_worker: Addr<MatchmakingWorker>,
     game_server: Addr<GameServer>,
     mail_worker: Addr<MailWorker>,
+    lobby: Addr<LobbyServer>,
 }
 
 #[actix_web::main]
@@ -31,7 +33,7 @@ async fn main() -> std::io::Result<()> {
     // Connect to Postgres backend
     let db_url = &*env::DATABASE_URL;
     let redis_url = &*env::REDIS_URL;
-    let ttt_db = TttDbConn::new(&db_url, &redis_url).await;
+    let ttt_db = TttDbConn::new(db_url, redis_url).await;
     let ttt_db_arc = Arc::new(ttt_db.clone());
 
     let mail_worker = MailWorker::new().await;
@@ -40,7 +42,10 @@ async fn main() -> std::io::Result<()> {
     let game_server = GameServer::new(ttt_db_arc.clone());
     let game_server = game_server.start();
 
-    let mm_worker = MatchmakingWorker::new(ttt_db_arc, game_server.clone());
+    let lobby = LobbyServer::new(ttt_db_arc.clone());
+    let lobby = lobby.start();
+
+    let mm_worker = MatchmakingWorker::new(ttt_db_arc, game_server.clone(), lobby.clone());
     let mm_worker = mm_worker.start();
 
     let state = AppState {
@@ -48,6 +53,7 @@ async fn main() -> std::io::Result<()> {
         mm_worker,
         game_server,
         mail_worker,
+        lobby,
     };
 
     // Add redis session store
@@ -67,17 +73,31 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(state.clone()))
             .service(
                 web::scope("/api/v1")
+                    .wrap(SessionGuard)
                     .configure(auth::init_routes)
+                    .configure(oidc::init_routes)
+                    .configure(two_factor::init_routes)
                     .configure(user::init_routes)
                     .configure(email_verify::init_routes)
                     .configure(matchmaking::init_routes)
                     .configure(data::init_routes)
                     .configure(elo::init_routes)
-                    .configure(game::init_routes),
+                    .configure(game::init_routes)
+                    .configure(games::init_routes)
+                    .configure(profile::init_routes)
+                    .configure(h2h::init_routes)
+                    .configure(stats::init_routes)
+                    .configure(rating_history::init_routes)
+                    .configure(friends::init_routes)
+                    .configure(lobby::init_routes)
+                    .configure(messages::init_routes)
+                    .configure(blocks::init_routes)
+                    .configure(reports::init_routes)
+                    .configure(admin::init_routes),
             )
             .configure(email_verify_front::init_routes)
     });
-    let host = &*env::HOST.as_str();
+    let host = env::HOST.as_str();
     let port = *env::PORT;
     let server = server.bind((host, port))?;
 
@@ -0,0 +1,314 @@
+use actix_session::Session;
+use actix_web::{delete, get, post, web, HttpResponse};
+use serde::Deserialize;
+use serde_json::json;
+use ttt_db::{
+    AuditAction, AuditLogFilter, Ban, BanRequest, NewAuditEntry, RatingAdjustment, TttDbErr,
+    UserSearch,
+};
+use ttt_game_server::game::game_info::Adjudication;
+use ttt_game_server::game::messages::{ForceEnd, GetGameInfo};
+use ttt_game_server::server::messages::{
+    GetActiveGames, GetGameAddress, KickUser as KickFromGames,
+};
+use ttt_lobby::server::messages::Kick;
+use ttt_lobby::LobbyEvent;
+use ttt_matchmaking::{GetConnectedUsers, KickUser as KickFromQueue};
+use uuid::Uuid;
+
+use crate::util::{SessionData, TttApiErr};
+use crate::AppState;
+
+#[derive(Deserialize)]
+struct EndGameRequest {
+    #[serde(flatten)]
+    adjudication: Adjudication,
+    reason: Option<String>,
+}
+
+/// Sends event to a user and closes all of their websocket connections.
+pub(crate) fn kick_user(data: &AppState, user_id: i64, event: LobbyEvent) {
+    data.lobby.do_send(Kick(user_id, event));
+    data.mm_worker.do_send(KickFromQueue(user_id));
+    data.game_server.do_send(KickFromGames(user_id));
+}
+
+/// Notifies a banned user and closes all of their websocket connections.
+pub(crate) fn kick_banned_user(data: &AppState, user_id: i64, ban: Ban) {
+    kick_user(data, user_id, LobbyEvent::Banned(ban));
+}
+
+#[get("/admin/users")]
+async fn search_users(
+    data: web::Data<AppState>,
+    session: Session,
+    search: web::Query<UserSearch>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let users = db.search_users(search.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(users))
+}
+
+#[post("/admin/users/{user_id}/ban")]
+async fn ban_user(
+    data: web::Data<AppState>,
+    session: Session,
+    user_id: web::Path<i64>,
+    req: web::Json<BanRequest>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin || user.id == *user_id {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let req = req.into_inner();
+    let before = Ban::from_user(&db.find_user_by_id(*user_id).await?);
+    let banned = db
+        .ban_user(*user_id, req.ban_ends, Some(req.reason.clone()))
+        .await?;
+    let after = Ban::from_user(&banned);
+    let entry = NewAuditEntry::new(user.id, AuditAction::Ban)
+        .user(*user_id)
+        .change(&before, &after)
+        .reason(Some(req.reason));
+    db.record_audit(entry).await?;
+    if let Some(ban) = after {
+        kick_banned_user(&data, banned.user_id, ban);
+    }
+    Ok(HttpResponse::Ok().json(banned))
+}
+
+#[delete("/admin/users/{user_id}/ban")]
+async fn unban_user(
+    data: web::Data<AppState>,
+    session: Session,
+    user_id: web::Path<i64>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let before = Ban::from_user(&db.find_user_by_id(*user_id).await?);
+    let unbanned = db.unban_user(*user_id).await?;
+    let entry = NewAuditEntry::new(user.id, AuditAction::Unban)
+        .user(*user_id)
+        .change(&before, &Ban::from_user(&unbanned));
+    db.record_audit(entry).await?;
+    Ok(HttpResponse::Ok().json(unbanned))
+}
+
+#[post("/admin/users/{user_id}/promote")]
+async fn promote_user(
+    data: web::Data<AppState>,
+    session: Session,
+    user_id: web::Path<i64>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let before = db.find_user_by_id(*user_id).await?.is_admin;
+    let target = db.set_admin(*user_id, true).await?;
+    let entry = NewAuditEntry::new(user.id, AuditAction::Promote)
+        .user(*user_id)
+        .change(
+            &json!({ "admin": before }),
+            &json!({ "admin": target.is_admin }),
+        );
+    db.record_audit(entry).await?;
+    Ok(HttpResponse::Ok().json(target))
+}
+
+#[post("/admin/users/{user_id}/demote")]
+async fn demote_user(
+    data: web::Data<AppState>,
+    session: Session,
+    user_id: web::Path<i64>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin || user.id == *user_id {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let before = db.find_user_by_id(*user_id).await?.is_admin;
+    let target = db.set_admin(*user_id, false).await?;
+    // Sessions store the admin flag, so the demoted user has to sign in again
+    db.invalidate_sessions(*user_id).await?;
+    let entry = NewAuditEntry::new(user.id, AuditAction::Demote)
+        .user(*user_id)
+        .change(
+            &json!({ "admin": before }),
+            &json!({ "admin": target.is_admin }),
+        );
+    db.record_audit(entry).await?;
+    Ok(HttpResponse::Ok().json(target))
+}
+
+#[post("/admin/users/{user_id}/verify")]
+async fn verify_user_email(
+    data: web::Data<AppState>,
+    session: Session,
+    user_id: web::Path<i64>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let before = db.find_user_by_id(*user_id).await?.email_verified;
+    let target = db.force_verify_email(*user_id).await?;
+    let entry = NewAuditEntry::new(user.id, AuditAction::VerifyEmail)
+        .user(*user_id)
+        .change(
+            &json!({ "email_verified": before }),
+            &json!({ "email_verified": target.email_verified }),
+        );
+    db.record_audit(entry).await?;
+    Ok(HttpResponse::Ok().json(target))
+}
+
+#[get("/admin/games")]
+async fn list_active_games(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let games = data
+        .game_server
+        .send(GetActiveGames)
+        .await
+        .map_err(|_| TttApiErr::unhandled())?;
+    Ok(HttpResponse::Ok().json(games))
+}
+
+#[post("/admin/games/{game_id}/end")]
+async fn end_game(
+    data: web::Data<AppState>,
+    session: Session,
+    game_id: web::Path<Uuid>,
+    req: web::Json<EndGameRequest>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let game = data
+        .game_server
+        .send(GetGameAddress(*game_id))
+        .await
+        .map_err(|_| TttApiErr::unhandled())?
+        .ok_or(TttDbErr::GameNotFound)?;
+    let req = req.into_inner();
+    let before = game
+        .send(GetGameInfo)
+        .await
+        .map_err(|_| TttDbErr::GameNotFound)?;
+    game.send(ForceEnd(req.adjudication))
+        .await
+        .map_err(|_| TttDbErr::GameNotFound)??;
+    let entry = NewAuditEntry::new(user.id, AuditAction::Adjudicate)
+        .game(*game_id)
+        .change(&before, &req.adjudication)
+        .reason(req.reason);
+    data.ttt_db.record_audit(entry).await?;
+    Ok(HttpResponse::Ok().json("Game ended."))
+}
+
+#[get("/admin/queue")]
+async fn list_queue(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let mut queue = db.get_mm_queue().await?;
+    let connected = data
+        .mm_worker
+        .send(GetConnectedUsers)
+        .await
+        .map_err(|_| TttApiErr::unhandled())?;
+    for entry in queue.iter_mut() {
+        entry.connected = connected.contains(&entry.user_id);
+    }
+    Ok(HttpResponse::Ok().json(queue))
+}
+
+#[delete("/admin/queue/{user_id}")]
+async fn kick_from_queue(
+    data: web::Data<AppState>,
+    session: Session,
+    user_id: web::Path<i64>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    data.mm_worker.do_send(KickFromQueue(*user_id));
+    let entry = NewAuditEntry::new(user.id, AuditAction::QueueKick).user(*user_id);
+    data.ttt_db.record_audit(entry).await?;
+    Ok(HttpResponse::Ok().json("User removed from the queue."))
+}
+
+#[post("/admin/users/{user_id}/rating")]
+async fn adjust_rating(
+    data: web::Data<AppState>,
+    session: Session,
+    user_id: web::Path<i64>,
+    req: web::Json<RatingAdjustment>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let req = req.into_inner();
+    let (before, after) = db.adjust_rating(*user_id, req.pool, req.elo).await?;
+    let entry = NewAuditEntry::new(user.id, AuditAction::RatingAdjustment)
+        .user(*user_id)
+        .change(&before, &after)
+        .reason(Some(req.reason));
+    db.record_audit(entry).await?;
+    Ok(HttpResponse::Ok().json(after))
+}
+
+#[get("/admin/audit-log")]
+async fn get_audit_log(
+    data: web::Data<AppState>,
+    session: Session,
+    filter: web::Query<AuditLogFilter>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let entries = db.get_audit_log(filter.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(search_users);
+    cfg.service(ban_user);
+    cfg.service(unban_user);
+    cfg.service(promote_user);
+    cfg.service(demote_user);
+    cfg.service(verify_user_email);
+    cfg.service(list_active_games);
+    cfg.service(end_game);
+    cfg.service(list_queue);
+    cfg.service(kick_from_queue);
+    cfg.service(adjust_rating);
+    cfg.service(get_audit_log);
+}
@@ -1,14 +1,33 @@
 use actix_session::Session;
 use actix_web::{post, web, HttpResponse};
-use ttt_mailer::SendVerificationEmail;
+use serde::Deserialize;
+use ttt_mailer::{SendPasswordResetEmail, SendVerificationEmail};
 use uuid::Uuid;
 
 use crate::AppState;
 use ttt_db::serializables::UserMessage;
+use ttt_db::{TttDbConn, TttDbErr};
 
+use crate::util::PendingTwoFactor;
 use crate::util::SessionData;
 use crate::util::TttApiErr;
 
+#[derive(Deserialize)]
+struct ForgotPassword {
+    email: String,
+}
+
+#[derive(Deserialize)]
+struct TwoFactorCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct ResetPassword {
+    token: Uuid,
+    password: String,
+}
+
 #[post("/auth/signup")]
 async fn sign_up(
     data: web::Data<AppState>,
@@ -32,20 +51,88 @@ async fn sign_in(
 ) -> Result<HttpResponse, TttApiErr> {
     let db = &data.ttt_db;
     let user = db.sign_in(req.into_inner()).await?;
-    if user.is_banned {
-        let ban_ends = match user.ban_ends {
-            Some(datetime) => datetime.format("%d/%m/%Y %R %Z").to_string(),
-            None => "the end of time".to_string(),
-        };
-        return Ok(HttpResponse::Forbidden().json(format!(
-            "Your account has been suspended until {}",
-            ban_ends
-        )));
+    start_session(
+        db,
+        &session,
+        user.user_id,
+        &user.username,
+        user.is_admin,
+        user.guest,
+    )
+    .await
+}
+
+/// Signs in a user who passed the first factor. Users with two-factor authentication
+/// get a pending session instead, which is completed with `/auth/2fa`.
+pub(crate) async fn start_session(
+    db: &TttDbConn,
+    session: &Session,
+    user_id: i64,
+    username: &str,
+    admin: bool,
+    guest: bool,
+) -> Result<HttpResponse, TttApiErr> {
+    if let Some(ban) = db.get_active_ban(user_id).await? {
+        return Err(TttApiErr::banned(&ban));
+    }
+    if db.has_two_factor(user_id).await? {
+        session.clear();
+        session.insert("pending_2fa", PendingTwoFactor::new(user_id))?;
+        session.renew();
+        return Ok(HttpResponse::Accepted().json("Two-factor authentication code required."));
     }
-    session.insert("id", &user.user_id)?;
+    session.remove("pending_2fa");
+    session.insert("id", user_id)?;
+    session.insert("username", username)?;
+    // Admin privileges are granted only to sessions verified with two-factor authentication
+    session.insert("admin", false)?;
+    session.insert("guest", guest)?;
+    session.insert("epoch", db.get_session_epoch(user_id).await?)?;
+    session.renew();
+    if admin {
+        return Ok(HttpResponse::Created().json(
+            "Signed in successfuly, enable two-factor authentication to use admin privileges.",
+        ));
+    }
+    Ok(HttpResponse::Created().json("Signed in successfuly"))
+}
+
+#[post("/auth/2fa")]
+async fn verify_two_factor(
+    data: web::Data<AppState>,
+    req: web::Json<TwoFactorCode>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let pending = session
+        .get::<PendingTwoFactor>("pending_2fa")
+        .ok()
+        .flatten();
+    let pending = match pending {
+        Some(pending) if !pending.is_expired() => pending,
+        _ => {
+            session.remove("pending_2fa");
+            return Err(TttApiErr::session_expired());
+        }
+    };
+    let db = &data.ttt_db;
+    if let Err(err) = db.verify_two_factor(pending.user_id, &req.code).await {
+        // Locked out users have to sign in again once the lockout expires
+        if let TttDbErr::TwoFactorLocked = err {
+            session.remove("pending_2fa");
+        }
+        return Err(err.into());
+    }
+    let user = db.find_user_by_id(pending.user_id).await?;
+    if let Some(ban) = db.get_active_ban(user.user_id).await? {
+        session.remove("pending_2fa");
+        return Err(TttApiErr::banned(&ban));
+    }
+    session.remove("pending_2fa");
+    session.insert("id", user.user_id)?;
     session.insert("username", &user.username)?;
-    session.insert("admin", &user.is_admin)?;
-    session.insert("guest", &user.guest)?;
+    session.insert("admin", user.is_admin)?;
+    session.insert("guest", user.guest)?;
+    session.insert("epoch", db.get_session_epoch(user.user_id).await?)?;
     session.renew();
     Ok(HttpResponse::Created().json("Signed in successfuly"))
 }
@@ -57,10 +144,11 @@ async fn sign_in_as_guest(
 ) -> Result<HttpResponse, TttApiErr> {
     let db = &data.ttt_db;
     let user = db.create_guest_user().await?;
-    session.insert("id", &user.user_id)?;
+    session.insert("id", user.user_id)?;
     session.insert("username", &user.username)?;
-    session.insert("admin", &user.is_admin)?;
-    session.insert("guest", &user.guest)?;
+    session.insert("admin", user.is_admin)?;
+    session.insert("guest", user.guest)?;
+    session.insert("epoch", db.get_session_epoch(user.user_id).await?)?;
     session.renew();
     Ok(HttpResponse::Created().json("Signed in successfuly"))
 }
@@ -78,10 +166,41 @@ async fn signout(session: Session) -> Result<HttpResponse, TttApiErr> {
     Ok(HttpResponse::Ok().json("Logged out"))
 }
 
+#[post("/auth/password/forgot")]
+async fn forgot_password(
+    data: web::Data<AppState>,
+    req: web::Json<ForgotPassword>,
+) -> Result<HttpResponse, TttApiErr> {
+    let db = &data.ttt_db;
+    let mailer = data.mail_worker.clone();
+    let f = move |username: String, email: String, uuid: Uuid| {
+        mailer.do_send(SendPasswordResetEmail::new(username, email, uuid))
+    };
+    db.request_password_reset(&req.email, f).await?;
+    Ok(HttpResponse::Ok()
+        .json("If an account with this email exists, a password reset code has been sent."))
+}
+
+#[post("/auth/password/reset")]
+async fn reset_password(
+    data: web::Data<AppState>,
+    req: web::Json<ResetPassword>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let db = &data.ttt_db;
+    let user_id = db.reset_password(req.token, &req.password).await?;
+    db.invalidate_sessions(user_id).await?;
+    session.purge();
+    Ok(HttpResponse::Ok().json("Password changed, please sign in again."))
+}
+
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(sign_up);
     cfg.service(sign_in);
     cfg.service(sign_in_as_guest);
+    cfg.service(verify_two_factor);
     cfg.service(signout);
     cfg.service(authorize_session);
+    cfg.service(forgot_password);
+    cfg.service(reset_password);
 }
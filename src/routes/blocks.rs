@@ -0,0 +1,46 @@
+use actix_session::Session;
+use actix_web::{delete, get, post, web, HttpResponse};
+
+use crate::util::{SessionData, TttApiErr};
+use crate::AppState;
+
+#[get("/blocks")]
+async fn list_blocked(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let blocked = db.get_blocked_users(user.id).await?;
+    Ok(HttpResponse::Ok().json(blocked))
+}
+
+#[post("/blocks/{username}")]
+async fn block_user(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.block_user(user.id, &username).await?;
+    Ok(HttpResponse::Ok().json("User blocked."))
+}
+
+#[delete("/blocks/{username}")]
+async fn unblock_user(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.unblock_user(user.id, &username).await?;
+    Ok(HttpResponse::Ok().json("User unblocked."))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_blocked);
+    cfg.service(block_user);
+    cfg.service(unblock_user);
+}
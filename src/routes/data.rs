@@ -1,5 +1,6 @@
 use actix_session::Session;
 use actix_web::{get, web, HttpResponse};
+use ttt_db::Pool;
 
 use crate::AppState;
 
@@ -14,10 +15,14 @@ async fn user_data(data: web::Data<AppState>, session: Session) -> Result<HttpRe
 }
 
 #[get("/elo")]
-async fn get_elo(data: web::Data<AppState>, session: Session) -> Result<HttpResponse, TttApiErr> {
+async fn get_elo(
+    data: web::Data<AppState>,
+    session: Session,
+    pool: web::Query<Pool>,
+) -> Result<HttpResponse, TttApiErr> {
     let user = session.get_data()?;
     let db = &data.ttt_db;
-    let elo = db.get_elo(user.id).await?;
+    let elo = db.get_elo(user.id, pool.into_inner()).await?;
     Ok(HttpResponse::Ok().json(elo))
 }
 
@@ -11,7 +11,7 @@ use uuid::Uuid;
 use crate::util::TttApiErr;
 use crate::AppState;
 
-static TEMPLATE: &'static str = r#"
+static TEMPLATE: &str = r#"
 <!DOCTYPE html><html><head><meta charset="UTF-8"><link rel="preconnect" href="https://fonts.googleapis.com"><link rel="preconnect" href="https://fonts.gstatic.com" crossorigin><link href="https://fonts.googleapis.com/css2?family=Roboto:wght@400&display=swap" rel="stylesheet"><title>Email verification</title></head><body style="background-color:#1e1e2e;margin:0;height:100vh;display:flex;justify-content:center;align-items:center"><p style="font-family:Roboto,sans-serif;color:#cdd6f4">{msg}</p></body></html>
 "#;
 
@@ -0,0 +1,110 @@
+use actix_session::Session;
+use actix_web::{delete, get, post, web, HttpResponse};
+use ttt_lobby::server::messages::Notify;
+use ttt_lobby::LobbyEvent;
+
+use crate::util::{SessionData, TttApiErr};
+use crate::AppState;
+
+#[get("/friends")]
+async fn list_friends(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let friends = db.get_friends(user.id).await?;
+    Ok(HttpResponse::Ok().json(friends))
+}
+
+#[get("/friends/requests")]
+async fn list_friend_requests(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let requests = db.get_friend_requests(user.id).await?;
+    Ok(HttpResponse::Ok().json(requests))
+}
+
+#[post("/friends/requests/{username}")]
+async fn send_friend_request(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let accepted = db.send_friend_request(user.id, &username).await?;
+    let friend = db.find_user_by_username(&username).await?;
+    if accepted {
+        let event = LobbyEvent::FriendRequestAccepted(user.username);
+        data.lobby.do_send(Notify(friend.user_id, event));
+        return Ok(HttpResponse::Ok().json("Friend request accepted."));
+    }
+    let event = LobbyEvent::FriendRequest(user.username);
+    data.lobby.do_send(Notify(friend.user_id, event));
+    Ok(HttpResponse::Created().json("Friend request sent."))
+}
+
+#[delete("/friends/requests/{username}")]
+async fn cancel_friend_request(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.cancel_friend_request(user.id, &username).await?;
+    Ok(HttpResponse::Ok().json("Friend request canceled."))
+}
+
+#[post("/friends/requests/{username}/accept")]
+async fn accept_friend_request(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.accept_friend_request(user.id, &username).await?;
+    let friend = db.find_user_by_username(&username).await?;
+    let event = LobbyEvent::FriendRequestAccepted(user.username);
+    data.lobby.do_send(Notify(friend.user_id, event));
+    Ok(HttpResponse::Ok().json("Friend request accepted."))
+}
+
+#[post("/friends/requests/{username}/decline")]
+async fn decline_friend_request(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.decline_friend_request(user.id, &username).await?;
+    Ok(HttpResponse::Ok().json("Friend request declined."))
+}
+
+#[delete("/friends/{username}")]
+async fn remove_friend(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.remove_friend(user.id, &username).await?;
+    Ok(HttpResponse::Ok().json("Friend removed."))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_friends);
+    cfg.service(list_friend_requests);
+    cfg.service(send_friend_request);
+    cfg.service(cancel_friend_request);
+    cfg.service(accept_friend_request);
+    cfg.service(decline_friend_request);
+    cfg.service(remove_friend);
+}
@@ -29,14 +29,14 @@ async fn enter_queue(
                 return Err(TttApiErr::forbidden());
             }
             let game_addr = addr.unwrap();
-            let ws = GameWs::new(user.id, game_addr);
+            let ws = GameWs::new(user.id, *game_id, game_addr, db.presence());
             let res = ws::start(ws, &req, stream);
             match res {
                 Ok(res) => Ok(res),
                 Err(_) => Err(TttApiErr::unhandled()),
             }
         }
-        Err(_) => return Err(TttApiErr::forbidden()),
+        Err(_) => Err(TttApiErr::forbidden()),
     }
 }
 
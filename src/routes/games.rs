@@ -0,0 +1,37 @@
+use actix_session::Session;
+use actix_web::{get, web, HttpResponse};
+use ttt_db::GameHistoryFilter;
+
+use crate::util::{SessionData, TttApiErr};
+use crate::AppState;
+
+#[get("/games")]
+async fn match_history(
+    data: web::Data<AppState>,
+    session: Session,
+    filter: web::Query<GameHistoryFilter>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let games = db.get_match_history(user.id, filter.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(games))
+}
+
+#[get("/users/{username}/games")]
+async fn user_match_history(
+    data: web::Data<AppState>,
+    username: web::Path<String>,
+    filter: web::Query<GameHistoryFilter>,
+) -> Result<HttpResponse, TttApiErr> {
+    let db = &data.ttt_db;
+    let user = db.find_user_by_username(&username).await?;
+    let games = db
+        .get_match_history(user.user_id, filter.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(games))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(match_history);
+    cfg.service(user_match_history);
+}
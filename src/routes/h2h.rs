@@ -0,0 +1,19 @@
+use actix_web::{get, web, HttpResponse};
+
+use crate::util::TttApiErr;
+use crate::AppState;
+
+#[get("/h2h/{username_a}/{username_b}")]
+async fn head_to_head(
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, TttApiErr> {
+    let (username_a, username_b) = path.into_inner();
+    let db = &data.ttt_db;
+    let h2h = db.get_head_to_head(&username_a, &username_b).await?;
+    Ok(HttpResponse::Ok().json(h2h))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(head_to_head);
+}
@@ -0,0 +1,44 @@
+use crate::util::TttApiErr;
+use crate::{util::SessionData, AppState};
+use actix_session::Session;
+use actix_web::{get, post, web, web::Payload, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use ttt_lobby::server::messages::Broadcast;
+use ttt_lobby::{LobbyEvent, LobbyWebsocket};
+
+#[get("/lobby")]
+async fn enter_lobby(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+    stream: Payload,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let ws = LobbyWebsocket::new(user.id, data.lobby.clone(), db.presence());
+    let res = ws::start(ws, &req, stream);
+    match res {
+        Ok(res) => Ok(res),
+        Err(_) => Err(TttApiErr::unhandled()),
+    }
+}
+
+#[post("/lobby/announcement")]
+async fn announce(
+    data: web::Data<AppState>,
+    session: Session,
+    req: web::Json<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let event = LobbyEvent::Announcement(req.into_inner());
+    data.lobby.do_send(Broadcast(event));
+    Ok(HttpResponse::Ok().json("Announcement sent."))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(enter_lobby);
+    cfg.service(announce);
+}
@@ -3,6 +3,7 @@ use crate::{util::SessionData, AppState};
 use actix_session::Session;
 use actix_web::{get, web, web::Payload, HttpRequest, HttpResponse};
 use actix_web_actors::ws;
+use ttt_db::Pool;
 use ttt_db::TttDbErr::UserAlreadyQueued;
 use ttt_matchmaking::ws::MatchmakingWebsocket as MmWs;
 
@@ -12,6 +13,7 @@ async fn enter_queue(
     req: HttpRequest,
     stream: Payload,
     session: Session,
+    pool: web::Query<Pool>,
 ) -> Result<HttpResponse, TttApiErr> {
     let user = session.get_data()?;
     let db = &data.ttt_db;
@@ -19,7 +21,7 @@ async fn enter_queue(
         return Err(UserAlreadyQueued.into());
     }
     let mm_worker = data.mm_worker.clone();
-    let ws = MmWs::new(user.id, mm_worker);
+    let ws = MmWs::new(user.id, pool.into_inner(), mm_worker, db.presence());
     let res = ws::start(ws, &req, stream);
     match res {
         Ok(res) => Ok(res),
@@ -0,0 +1,161 @@
+use actix_session::Session;
+use actix_web::{delete, get, post, web, HttpResponse};
+use serde::Deserialize;
+use serde_json::json;
+use ttt_db::{AuditAction, MessagePage, NewAuditEntry};
+use ttt_lobby::server::messages::Notify;
+use ttt_lobby::LobbyEvent;
+
+use crate::util::{SessionData, TttApiErr};
+use crate::AppState;
+
+#[derive(Deserialize)]
+struct NewMessage {
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct ReportResolution {
+    remove: bool,
+}
+
+#[get("/messages")]
+async fn list_conversations(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let conversations = db.get_conversations(user.id).await?;
+    Ok(HttpResponse::Ok().json(conversations))
+}
+
+#[get("/messages/{username}")]
+async fn get_conversation(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+    page: web::Query<MessagePage>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let messages = db
+        .get_conversation(user.id, &username, page.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(messages))
+}
+
+#[post("/messages/{username}")]
+async fn send_message(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+    req: web::Json<NewMessage>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let sent = db
+        .send_direct_message(user.id, &username, &req.body)
+        .await?;
+    if sent.deliver {
+        let event = LobbyEvent::DirectMessage(sent.message.clone());
+        data.lobby.do_send(Notify(sent.recipient_id, event));
+    }
+    Ok(HttpResponse::Created().json(sent.message))
+}
+
+#[post("/messages/{message_id}/report")]
+async fn report_message(
+    data: web::Data<AppState>,
+    session: Session,
+    message_id: web::Path<i64>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.report_message(user.id, *message_id).await?;
+    Ok(HttpResponse::Ok().json("Message reported."))
+}
+
+#[get("/mutes")]
+async fn list_muted(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let muted = db.get_muted_users(user.id).await?;
+    Ok(HttpResponse::Ok().json(muted))
+}
+
+#[post("/mutes/{username}")]
+async fn mute_user(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.mute_user(user.id, &username).await?;
+    Ok(HttpResponse::Ok().json("User muted."))
+}
+
+#[delete("/mutes/{username}")]
+async fn unmute_user(
+    data: web::Data<AppState>,
+    session: Session,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.unmute_user(user.id, &username).await?;
+    Ok(HttpResponse::Ok().json("User unmuted."))
+}
+
+#[get("/admin/messages/reported")]
+async fn list_reported_messages(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let messages = db.get_reported_messages().await?;
+    Ok(HttpResponse::Ok().json(messages))
+}
+
+#[post("/admin/messages/{message_id}/resolve")]
+async fn resolve_message_report(
+    data: web::Data<AppState>,
+    session: Session,
+    message_id: web::Path<i64>,
+    req: web::Json<ReportResolution>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let sender_id = db.resolve_message_report(*message_id, req.remove).await?;
+    let entry = NewAuditEntry::new(user.id, AuditAction::MessageModeration)
+        .user(sender_id)
+        .change(
+            &json!({ "message_id": *message_id, "reported": true }),
+            &json!({ "message_id": *message_id, "removed": req.remove }),
+        );
+    db.record_audit(entry).await?;
+    Ok(HttpResponse::Ok().json("Report resolved."))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_conversations);
+    cfg.service(get_conversation);
+    cfg.service(send_message);
+    cfg.service(report_message);
+    cfg.service(list_muted);
+    cfg.service(mute_user);
+    cfg.service(unmute_user);
+    cfg.service(list_reported_messages);
+    cfg.service(resolve_message_report);
+}
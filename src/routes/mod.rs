@@ -1,8 +1,21 @@
+pub(crate) mod admin;
 pub(crate) mod auth;
+pub(crate) mod blocks;
 pub(crate) mod data;
 pub(crate) mod elo;
 pub(crate) mod email_verify;
 pub(crate) mod email_verify_front;
+pub(crate) mod friends;
 pub(crate) mod game;
+pub(crate) mod games;
+pub(crate) mod h2h;
+pub(crate) mod lobby;
 pub(crate) mod matchmaking;
+pub(crate) mod messages;
+pub(crate) mod oidc;
+pub(crate) mod profile;
+pub(crate) mod rating_history;
+pub(crate) mod reports;
+pub(crate) mod stats;
+pub(crate) mod two_factor;
 pub(crate) mod user;
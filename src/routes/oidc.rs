@@ -0,0 +1,129 @@
+use actix_session::Session;
+use actix_web::http::header;
+use actix_web::{delete, get, web, HttpResponse};
+use serde::Deserialize;
+use ttt_db::TttDbErr;
+
+use crate::routes::auth::start_session;
+use crate::util::env::OIDC_PROVIDERS;
+use crate::util::oidc::{find_provider, PendingSignIn};
+use crate::util::{SessionData, TttApiErr};
+use crate::AppState;
+
+#[derive(Deserialize)]
+struct Callback {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+fn redirect_to_provider(
+    session: &Session,
+    provider: &str,
+    link_user: Option<i64>,
+) -> Result<HttpResponse, TttApiErr> {
+    let provider = match find_provider(provider) {
+        Some(provider) => provider,
+        None => return Ok(HttpResponse::NotFound().json("Unknown identity provider.")),
+    };
+    let (url, pending) = provider.start_sign_in(link_user)?;
+    session.insert("oidc", pending)?;
+    Ok(HttpResponse::Found()
+        .insert_header((header::LOCATION, url.to_string()))
+        .finish())
+}
+
+#[get("/auth/oidc/providers")]
+async fn get_providers() -> Result<HttpResponse, TttApiErr> {
+    Ok(HttpResponse::Ok().json(&*OIDC_PROVIDERS))
+}
+
+#[get("/auth/oidc/{provider}/login")]
+async fn sign_in(provider: web::Path<String>, session: Session) -> Result<HttpResponse, TttApiErr> {
+    redirect_to_provider(&session, &provider, None)
+}
+
+#[get("/auth/oidc/{provider}/link")]
+async fn link(provider: web::Path<String>, session: Session) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if user.guest {
+        return Err(TttDbErr::GuestNotAllowed.into());
+    }
+    redirect_to_provider(&session, &provider, Some(user.id))
+}
+
+#[get("/auth/oidc/{provider}/callback")]
+async fn callback(
+    data: web::Data<AppState>,
+    provider: web::Path<String>,
+    query: web::Query<Callback>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let provider = match find_provider(&provider) {
+        Some(provider) => provider,
+        None => return Ok(HttpResponse::NotFound().json("Unknown identity provider.")),
+    };
+    // Pending sign in can only be used once
+    let pending = session.get::<PendingSignIn>("oidc").ok().flatten();
+    session.remove("oidc");
+    let pending = pending.ok_or_else(TttApiErr::sign_in_failed)?;
+    if query.error.is_some() {
+        return Err(TttApiErr::sign_in_failed());
+    }
+    let (code, state) = match (&query.code, &query.state) {
+        (Some(code), Some(state)) => (code, state),
+        _ => return Err(TttApiErr::sign_in_failed()),
+    };
+    let identity = provider.finish_sign_in(&pending, state, code).await?;
+    let db = &data.ttt_db;
+    if let Some(user_id) = pending.link_user {
+        let user = session.get_data()?;
+        if user.id != user_id {
+            return Err(TttApiErr::sign_in_failed());
+        }
+        db.link_identity(user_id, identity).await?;
+        return Ok(HttpResponse::Created().json("Account linked successfuly"));
+    }
+    let user = db.sign_in_with_identity(identity).await?;
+    start_session(
+        db,
+        &session,
+        user.user_id,
+        &user.username,
+        user.is_admin,
+        user.guest,
+    )
+    .await
+}
+
+#[get("/user/identities")]
+async fn get_identities(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let identities = db.get_identities(user.id).await?;
+    Ok(HttpResponse::Ok().json(identities))
+}
+
+#[delete("/user/identities/{provider}")]
+async fn unlink(
+    data: web::Data<AppState>,
+    provider: web::Path<String>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.unlink_identity(user.id, &provider).await?;
+    Ok(HttpResponse::Ok().json("Account unlinked"))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_providers);
+    cfg.service(sign_in);
+    cfg.service(link);
+    cfg.service(callback);
+    cfg.service(get_identities);
+    cfg.service(unlink);
+}
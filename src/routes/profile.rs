@@ -0,0 +1,30 @@
+use actix_web::{get, web, HttpResponse};
+
+use crate::util::TttApiErr;
+use crate::AppState;
+
+#[get("/profile/{username}")]
+async fn get_profile(
+    data: web::Data<AppState>,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let db = &data.ttt_db;
+    let profile = db.get_profile(&username).await?;
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+#[get("/users/{username}/status")]
+async fn get_status(
+    data: web::Data<AppState>,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let db = &data.ttt_db;
+    let user = db.find_user_by_username(&username).await?;
+    let status = db.get_user_status(user.user_id).await?;
+    Ok(HttpResponse::Ok().json(status))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_profile);
+    cfg.service(get_status);
+}
@@ -0,0 +1,22 @@
+use actix_web::{get, web, HttpResponse};
+use ttt_db::RatingHistoryFilter;
+
+use crate::util::TttApiErr;
+use crate::AppState;
+
+#[get("/users/{username}/rating-history")]
+async fn rating_history(
+    data: web::Data<AppState>,
+    username: web::Path<String>,
+    filter: web::Query<RatingHistoryFilter>,
+) -> Result<HttpResponse, TttApiErr> {
+    let db = &data.ttt_db;
+    let history = db
+        .get_rating_history(&username, filter.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(history))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(rating_history);
+}
@@ -0,0 +1,106 @@
+use actix_session::Session;
+use actix_web::{get, post, web, HttpResponse};
+use serde_json::json;
+use ttt_db::{AuditAction, NewAuditEntry, NewReport, ReportFilter, ReportResolution};
+
+use super::admin::kick_banned_user;
+use crate::util::{SessionData, TttApiErr};
+use crate::AppState;
+
+#[post("/report")]
+async fn create_report(
+    data: web::Data<AppState>,
+    session: Session,
+    req: web::Json<NewReport>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.create_report(user.id, req.into_inner()).await?;
+    Ok(HttpResponse::Created().json("Report submitted."))
+}
+
+#[get("/admin/reports")]
+async fn list_reports(
+    data: web::Data<AppState>,
+    session: Session,
+    filter: web::Query<ReportFilter>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let reports = db.get_reports(filter.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(reports))
+}
+
+#[get("/admin/reports/{report_id}")]
+async fn get_report(
+    data: web::Data<AppState>,
+    session: Session,
+    report_id: web::Path<i64>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let report = db.get_report(*report_id).await?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[post("/admin/reports/{report_id}/assign")]
+async fn assign_report(
+    data: web::Data<AppState>,
+    session: Session,
+    report_id: web::Path<i64>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    db.assign_report(*report_id, user.id).await?;
+    Ok(HttpResponse::Ok().json("Report assigned."))
+}
+
+#[post("/admin/reports/{report_id}/resolve")]
+async fn resolve_report(
+    data: web::Data<AppState>,
+    session: Session,
+    report_id: web::Path<i64>,
+    req: web::Json<ReportResolution>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    if !user.admin {
+        return Err(TttApiErr::forbidden());
+    }
+    let db = &data.ttt_db;
+    let req = req.into_inner();
+    let reason = req.note.clone();
+    let before = db.get_report(*report_id).await?;
+    let report = db.resolve_report(*report_id, user.id, req).await?;
+    let entry = NewAuditEntry::new(user.id, AuditAction::ReportResolution)
+        .user(report.reported_id)
+        .report(report.id)
+        .change(
+            &json!({ "status": before.status }),
+            &json!({ "status": report.status, "banned": report.banned }),
+        )
+        .reason(reason);
+    db.record_audit(entry).await?;
+    if report.banned {
+        if let Some(ban) = db.get_active_ban(report.reported_id).await? {
+            kick_banned_user(&data, report.reported_id, ban);
+        }
+    }
+    Ok(HttpResponse::Ok().json("Report resolved."))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_report);
+    cfg.service(list_reports);
+    cfg.service(get_report);
+    cfg.service(assign_report);
+    cfg.service(resolve_report);
+}
@@ -0,0 +1,26 @@
+use actix_web::{get, web, HttpResponse};
+
+use crate::util::TttApiErr;
+use crate::AppState;
+
+#[get("/stats")]
+async fn global_stats(data: web::Data<AppState>) -> Result<HttpResponse, TttApiErr> {
+    let db = &data.ttt_db;
+    let stats = db.get_global_stats().await?;
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+#[get("/users/{username}/stats")]
+async fn user_stats(
+    data: web::Data<AppState>,
+    username: web::Path<String>,
+) -> Result<HttpResponse, TttApiErr> {
+    let db = &data.ttt_db;
+    let stats = db.get_user_gameplay_stats(&username).await?;
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(global_stats);
+    cfg.service(user_stats);
+}
@@ -0,0 +1,78 @@
+use actix_session::Session;
+use actix_web::{get, post, web, HttpResponse};
+use serde::Deserialize;
+
+use crate::util::{SessionData, TttApiErr};
+use crate::AppState;
+
+#[derive(Deserialize)]
+struct TwoFactorCode {
+    code: String,
+}
+
+#[get("/user/2fa")]
+async fn get_status(
+    data: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let status = db.get_two_factor_status(user.id).await?;
+    Ok(HttpResponse::Ok().json(status))
+}
+
+#[post("/user/2fa/enroll")]
+async fn enroll(data: web::Data<AppState>, session: Session) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let enrollment = db.enroll_totp(user.id).await?;
+    Ok(HttpResponse::Created().json(enrollment))
+}
+
+#[post("/user/2fa/confirm")]
+async fn confirm(
+    data: web::Data<AppState>,
+    req: web::Json<TwoFactorCode>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let recovery_codes = db.confirm_totp(user.id, &req.code).await?;
+    // Session was just verified with a code, so admins get their privileges
+    let user = db.find_user_by_id(user.id).await?;
+    session.insert("admin", user.is_admin)?;
+    session.renew();
+    Ok(HttpResponse::Ok().json(recovery_codes))
+}
+
+#[post("/user/2fa/recovery-codes")]
+async fn regenerate_recovery_codes(
+    data: web::Data<AppState>,
+    req: web::Json<TwoFactorCode>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let recovery_codes = db.regenerate_recovery_codes(user.id, &req.code).await?;
+    Ok(HttpResponse::Ok().json(recovery_codes))
+}
+
+#[post("/user/2fa/disable")]
+async fn disable(
+    data: web::Data<AppState>,
+    req: web::Json<TwoFactorCode>,
+    session: Session,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.disable_two_factor(user.id, &req.code).await?;
+    Ok(HttpResponse::Ok().json("Two-factor authentication disabled."))
+}
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_status);
+    cfg.service(enroll);
+    cfg.service(confirm);
+    cfg.service(regenerate_recovery_codes);
+    cfg.service(disable);
+}
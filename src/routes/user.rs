@@ -1,13 +1,34 @@
 use actix_session::Session;
-use actix_web::{get, post, web, HttpResponse};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{delete, get, post, web, HttpResponse};
+use serde::Deserialize;
 use ttt_db::serializables::UserMessage;
-use ttt_mailer::SendVerificationEmail;
+use ttt_lobby::LobbyEvent;
+use ttt_mailer::{SendEmailChangedNotice, SendVerificationEmail};
 use uuid::Uuid;
 
+use super::admin::kick_user;
 use crate::util::{SessionData, TttApiErr};
 use crate::AppState;
 
-#[get("/user/{user_id}")]
+#[derive(Deserialize)]
+struct ChangePassword {
+    current_password: String,
+    new_password: String,
+}
+
+#[derive(Deserialize)]
+struct ChangeUsername {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct ChangeEmail {
+    email: String,
+    password: String,
+}
+
+#[get("/user/{user_id:\\d+}")]
 async fn find_by_id(
     data: web::Data<AppState>,
     user_id: web::Path<i64>,
@@ -44,15 +65,103 @@ async fn claim_guest_account(
         mailer.do_send(SendVerificationEmail::new(username, email, uuid))
     };
     let user = db.claim_guest_account(user.id, req.into_inner(), f).await?;
-    session.insert("id", &user.user_id)?;
+    session.insert("id", user.user_id)?;
     session.insert("username", &user.username)?;
-    session.insert("admin", &user.is_admin)?;
-    session.insert("guest", &user.guest)?;
+    session.insert("admin", user.is_admin)?;
+    session.insert("guest", user.guest)?;
     Ok(HttpResponse::Created().json("Account successfuly claimed."))
 }
 
+#[post("/user/password")]
+async fn change_password(
+    session: Session,
+    data: web::Data<AppState>,
+    req: web::Json<ChangePassword>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.change_password(user.id, &req.current_password, &req.new_password)
+        .await?;
+    // Sign out every other session, this one stays valid
+    let epoch = db.invalidate_sessions(user.id).await?;
+    session.insert("epoch", epoch)?;
+    session.renew();
+    Ok(HttpResponse::Ok().json("Password changed."))
+}
+
+#[post("/user/username")]
+async fn change_username(
+    session: Session,
+    data: web::Data<AppState>,
+    req: web::Json<ChangeUsername>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let user = db.change_username(user.id, &req.username).await?;
+    session.insert("username", &user.username)?;
+    Ok(HttpResponse::Ok().json("Username changed."))
+}
+
+#[post("/user/email")]
+async fn change_email(
+    session: Session,
+    data: web::Data<AppState>,
+    req: web::Json<ChangeEmail>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let mailer = data.mail_worker.clone();
+    let f = move |username: String, email: String, uuid: Uuid| {
+        mailer.do_send(SendVerificationEmail::new(username, email, uuid))
+    };
+    let old_email = db
+        .change_email(user.id, &req.password, &req.email, f)
+        .await?;
+    data.mail_worker.do_send(SendEmailChangedNotice::new(
+        user.username,
+        old_email,
+        req.email.trim().to_string(),
+    ));
+    Ok(HttpResponse::Ok().json("Email changed, please verify your new email."))
+}
+
+#[delete("/user")]
+async fn delete_account(
+    session: Session,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    db.delete_user(user.id).await?;
+    kick_user(&data, user.id, LobbyEvent::AccountDeleted);
+    session.purge();
+    Ok(HttpResponse::Ok().json("Account deleted."))
+}
+
+#[get("/user/export")]
+async fn export_data(
+    session: Session,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, TttApiErr> {
+    let user = session.get_data()?;
+    let db = &data.ttt_db;
+    let export = db.export_user_data(user.id).await?;
+    let filename = format!("ttt-{}.json", user.username);
+    Ok(HttpResponse::Ok()
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(filename)],
+        })
+        .json(export))
+}
+
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(find_by_id);
     cfg.service(get_session_data);
     cfg.service(claim_guest_account);
+    cfg.service(change_password);
+    cfg.service(change_username);
+    cfg.service(change_email);
+    cfg.service(delete_account);
+    cfg.service(export_data);
 }
@@ -3,6 +3,9 @@ use dotenv::dotenv;
 use lazy_static::lazy_static;
 use log::{error, info, warn};
 use std::{env, process::exit};
+use ttt_db::{RatingSystem, RATING_SYSTEM};
+
+use crate::util::oidc::OidcProvider;
 
 lazy_static! {
     pub static ref HOST: String = env::var("HOST").unwrap_or("localhost".to_string());
@@ -15,6 +18,14 @@ lazy_static! {
         });
     pub static ref DOMAIN: String =
         env::var("DOMAIN").unwrap_or(format!("{}:{}", *HOST, *PORT).to_string());
+    pub static ref SCHEME: String = match env::var("SCHEME").as_deref() {
+        Ok("http") => "http".to_string(),
+        Ok("https") | Err(_) => "https".to_string(),
+        Ok(_) => {
+            error!("Error parsing SCHEME env variable! Valid values are http and https");
+            exit(1);
+        }
+    };
     pub static ref STDOUT_MAIL: bool = env::var("STDOUT_MAIL")
         .unwrap_or("1".to_string())
         .parse::<bool>()
@@ -64,7 +75,7 @@ lazy_static! {
         });
         if key.is_empty() {
             Key::generate()
-        } else if key.as_bytes().len() < 64 {
+        } else if key.len() < 64 {
             warn!("SESSION_SECRET must be at least 512 bytes long!");
             warn!("Generating random session key...");
             Key::generate()
@@ -77,6 +88,18 @@ lazy_static! {
             error!("PASSWORD_HASH_SECRET environment variable not set!");
             exit(1);
         });
+    pub static ref OIDC_PROVIDERS: Vec<OidcProvider> = env::var("OIDC_PROVIDERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            OidcProvider::from_env(name).unwrap_or_else(|err| {
+                error!("{}", err);
+                exit(1);
+            })
+        })
+        .collect();
 }
 
 pub fn init_env() {
@@ -85,6 +108,7 @@ pub fn init_env() {
     let _x = &*HOST;
     let _x = &*PORT;
     let _x = &*DOMAIN;
+    let _x = &*SCHEME;
     let _x = &*STDOUT_MAIL;
     let _x = &*MAIL_SERVER;
     let _x = &*MAIL_USERNAME;
@@ -93,5 +117,12 @@ pub fn init_env() {
     let _x = &*REDIS_URL;
     let _x = &*SESSION_SECRET;
     let _x = &*PASSWORD_HASH_SECRET;
+    // Rating system is read by ttt-db, it is only validated here
+    if RatingSystem::from_env().is_err() {
+        error!("Error parsing RATING_SYSTEM env variable! Valid values are elo and glicko2");
+        exit(1);
+    }
+    let _x = &*RATING_SYSTEM;
+    let _x = &*OIDC_PROVIDERS;
     info!("Environment variables initialized successfuly!");
 }
@@ -3,6 +3,7 @@ use std::fmt::Display;
 
 use actix_session::SessionInsertError;
 use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use ttt_db::Ban;
 use ttt_db::TttDbErr::{self, *};
 
 #[derive(Debug, Clone)]
@@ -24,6 +25,34 @@ impl TttApiErr {
             body: "Forbidden".into(),
         }
     }
+    pub fn banned(ban: &Ban) -> Self {
+        let ban_ends = match ban.ban_ends {
+            Some(datetime) => datetime.format("%d/%m/%Y %R %Z").to_string(),
+            None => "the end of time".to_string(),
+        };
+        Self {
+            status_code: StatusCode::FORBIDDEN,
+            body: format!("Your account has been suspended until {}", ban_ends),
+        }
+    }
+    pub fn session_expired() -> Self {
+        Self {
+            status_code: StatusCode::UNAUTHORIZED,
+            body: "Session expired, please sign in again.".into(),
+        }
+    }
+    pub fn sign_in_failed() -> Self {
+        Self {
+            status_code: StatusCode::BAD_REQUEST,
+            body: "Sign in request is invalid or expired.".into(),
+        }
+    }
+    pub fn identity_provider() -> Self {
+        Self {
+            status_code: StatusCode::BAD_GATEWAY,
+            body: "Identity provider is unavailable.".into(),
+        }
+    }
     pub fn unhandled() -> Self {
         Self {
             status_code: StatusCode::INTERNAL_SERVER_ERROR,
@@ -34,7 +63,7 @@ impl TttApiErr {
 
 impl ResponseError for TttApiErr {
     fn status_code(&self) -> StatusCode {
-        self.status_code.clone()
+        self.status_code
     }
     fn error_response(&self) -> HttpResponse {
         HttpResponse::build(self.status_code()).json(self.body.clone())
@@ -52,6 +81,18 @@ impl From<TttDbErr> for TttApiErr {
             EmailVerifyNotFound => StatusCode::NOT_FOUND,
             EmailVerifyExpired => StatusCode::GONE,
             UserAlreadyQueued => StatusCode::CONFLICT,
+            GuestNotAllowed => StatusCode::FORBIDDEN,
+            FriendRequestNotFound => StatusCode::NOT_FOUND,
+            FriendshipExists => StatusCode::CONFLICT,
+            MessageNotFound => StatusCode::NOT_FOUND,
+            UserBlocked => StatusCode::FORBIDDEN,
+            ReportNotFound => StatusCode::NOT_FOUND,
+            GameNotFound => StatusCode::NOT_FOUND,
+            PasswordResetInvalid => StatusCode::BAD_REQUEST,
+            IdentityLinked => StatusCode::CONFLICT,
+            TwoFactorInvalid => StatusCode::UNAUTHORIZED,
+            TwoFactorLocked => StatusCode::TOO_MANY_REQUESTS,
+            InvalidInput(_) => StatusCode::BAD_REQUEST,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
         Self { status_code, body }
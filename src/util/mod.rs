@@ -1,6 +1,9 @@
 pub mod env;
 mod error;
+pub mod oidc;
 mod session;
+mod session_guard;
 
 pub use error::*;
 pub use session::*;
+pub use session_guard::SessionGuard;
@@ -0,0 +1,258 @@
+use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use awc::Client;
+use lazy_static::lazy_static;
+use log::warn;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ttt_db::ExternalIdentity;
+use url::Url;
+
+use crate::util::env::{DOMAIN, OIDC_PROVIDERS, SCHEME};
+use crate::util::TttApiErr;
+
+/// OpenID Connect provider users can sign in with.
+/// Any provider supporting the authorization code flow with PKCE works,
+/// including a local mock identity provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct OidcProvider {
+    pub name: String,
+    pub display_name: String,
+    #[serde(skip)]
+    issuer: String,
+    #[serde(skip)]
+    client_id: String,
+    #[serde(skip)]
+    client_secret: Option<String>,
+    #[serde(skip)]
+    auth_url: String,
+    #[serde(skip)]
+    token_url: String,
+    #[serde(skip)]
+    redirect_uri: String,
+    #[serde(skip)]
+    scopes: String,
+}
+
+/// Sign in started by a user, kept in their session until the provider redirects back.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingSignIn {
+    pub provider: String,
+    state: String,
+    nonce: String,
+    code_verifier: String,
+    /// Set if the identity should be linked to this user instead of signing in
+    pub link_user: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    aud: Audience,
+    exp: u64,
+    nonce: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl OidcProvider {
+    /// Reads configuration of a provider from `OIDC_<NAME>_*` env variables.
+    pub fn from_env(name: &str) -> Result<Self, String> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"^[a-z0-9_]+$").unwrap();
+        }
+        if !RE.is_match(name) {
+            return Err(format!(
+                "Invalid OIDC provider name {}, use lowercase alphanumeric characters",
+                name
+            ));
+        }
+        let prefix = format!("OIDC_{}_", name.to_uppercase());
+        let var = |key: &str| env::var(format!("{}{}", prefix, key)).ok();
+        let required = |key: &str| var(key).ok_or(format!("{}{} not set!", prefix, key));
+        let issuer = required("ISSUER")?.trim_end_matches('/').to_string();
+        let token_url = var("TOKEN_URL").unwrap_or(format!("{}/token", issuer));
+        // ID tokens are trusted because they come from the token endpoint,
+        // plain http is only allowed when explicitly enabled for a local mock provider
+        let allow_insecure = var("ALLOW_INSECURE").map_or(Ok(false), |v| {
+            v.parse::<bool>()
+                .map_err(|_| format!("Error parsing {}ALLOW_INSECURE env variable!", prefix))
+        })?;
+        let scheme = Url::parse(&token_url)
+            .map_err(|err| format!("Invalid {}TOKEN_URL {}: {}", prefix, token_url, err))?
+            .scheme()
+            .to_string();
+        if scheme != "https" && !(allow_insecure && scheme == "http") {
+            return Err(format!(
+                "Token endpoint of {} must use https, set {}ALLOW_INSECURE=true for a local mock provider",
+                name, prefix
+            ));
+        }
+        if allow_insecure {
+            warn!("Insecure token endpoint allowed for OIDC provider {}", name);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            display_name: var("DISPLAY_NAME").unwrap_or(name.to_string()),
+            client_id: required("CLIENT_ID")?,
+            client_secret: var("CLIENT_SECRET").filter(|s| !s.is_empty()),
+            auth_url: var("AUTH_URL").unwrap_or(format!("{}/authorize", issuer)),
+            token_url,
+            redirect_uri: var("REDIRECT_URI").unwrap_or(format!(
+                "{}://{}/api/v1/auth/oidc/{}/callback",
+                *SCHEME, *DOMAIN, name
+            )),
+            scopes: var("SCOPES").unwrap_or("openid email".to_string()),
+            issuer,
+        })
+    }
+    /// Starts a sign in, returns the url of the provider to redirect the user to.
+    pub fn start_sign_in(&self, link_user: Option<i64>) -> Result<(Url, PendingSignIn), TttApiErr> {
+        let pending = PendingSignIn {
+            provider: self.name.clone(),
+            state: random_string(32),
+            nonce: random_string(32),
+            code_verifier: random_string(64),
+            link_user,
+        };
+        let code_challenge = base64::encode_config(
+            Sha256::digest(pending.code_verifier.as_bytes()),
+            base64::URL_SAFE_NO_PAD,
+        );
+        let url = Url::parse_with_params(
+            &self.auth_url,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("scope", self.scopes.as_str()),
+                ("state", pending.state.as_str()),
+                ("nonce", pending.nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|err| {
+            warn!("Invalid authorization url of {}: {}", self.name, err);
+            TttApiErr::unhandled()
+        })?;
+        Ok((url, pending))
+    }
+    /// Exchanges the authorization code for an ID token and returns the identity in it.
+    pub async fn finish_sign_in(
+        &self,
+        pending: &PendingSignIn,
+        state: &str,
+        code: &str,
+    ) -> Result<ExternalIdentity, TttApiErr> {
+        if pending.provider != self.name || pending.state != state {
+            return Err(TttApiErr::sign_in_failed());
+        }
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("client_id", self.client_id.as_str()),
+            ("code_verifier", pending.code_verifier.as_str()),
+        ];
+        if let Some(secret) = &self.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+        let mut res = Client::default()
+            .post(&self.token_url)
+            .send_form(&form)
+            .await
+            .map_err(|err| {
+                warn!("Error reaching token endpoint of {}: {}", self.name, err);
+                TttApiErr::identity_provider()
+            })?;
+        if !res.status().is_success() {
+            warn!(
+                "Token endpoint of {} responded with {}",
+                self.name,
+                res.status()
+            );
+            return Err(TttApiErr::sign_in_failed());
+        }
+        let token = res.json::<TokenResponse>().await.map_err(|err| {
+            warn!("Invalid token response of {}: {}", self.name, err);
+            TttApiErr::identity_provider()
+        })?;
+        let claims = self.verify_id_token(&token.id_token, &pending.nonce)?;
+        Ok(ExternalIdentity {
+            provider: self.name.clone(),
+            subject: claims.sub,
+            email: claims.email.filter(|_| claims.email_verified == Some(true)),
+        })
+    }
+    /// ID token is received directly from the token endpoint, so its signature is not checked
+    /// and the connection to the provider is trusted instead (OpenID Connect Core 3.1.3.7).
+    fn verify_id_token(&self, id_token: &str, nonce: &str) -> Result<IdTokenClaims, TttApiErr> {
+        let invalid = |reason: &str| {
+            warn!("Invalid ID token from {}: {}", self.name, reason);
+            TttApiErr::sign_in_failed()
+        };
+        let payload = id_token
+            .split('.')
+            .nth(1)
+            .ok_or_else(|| invalid("malformed token"))?;
+        let payload = payload.trim_end_matches('=');
+        let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| invalid("malformed payload"))?;
+        let claims: IdTokenClaims =
+            serde_json::from_slice(&payload).map_err(|_| invalid("malformed claims"))?;
+        if claims.iss.trim_end_matches('/') != self.issuer {
+            return Err(invalid("issuer mismatch"));
+        }
+        let audience = match &claims.aud {
+            Audience::One(aud) => aud == &self.client_id,
+            Audience::Many(aud) => aud.contains(&self.client_id),
+        };
+        if !audience {
+            return Err(invalid("audience mismatch"));
+        }
+        if claims.exp <= now() {
+            return Err(invalid("token expired"));
+        }
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid("nonce mismatch"));
+        }
+        Ok(claims)
+    }
+}
+
+pub fn find_provider(name: &str) -> Option<&'static OidcProvider> {
+    OIDC_PROVIDERS.iter().find(|p| p.name == name)
+}
@@ -1,3 +1,5 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use actix_session::Session;
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +14,35 @@ pub struct UserSession {
     pub guest: bool,
 }
 
+/// Sign in waiting for the second factor.
+/// Session keys of the user are set only after the code is verified.
+#[derive(Serialize, Deserialize)]
+pub struct PendingTwoFactor {
+    pub user_id: i64,
+    expires_at: u64,
+}
+
+const PENDING_TWO_FACTOR_SECONDS: u64 = 300;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl PendingTwoFactor {
+    pub fn new(user_id: i64) -> Self {
+        Self {
+            user_id,
+            expires_at: now() + PENDING_TWO_FACTOR_SECONDS,
+        }
+    }
+    pub fn is_expired(&self) -> bool {
+        now() >= self.expires_at
+    }
+}
+
 pub trait SessionData {
     fn get_data(&self) -> Result<UserSession, TttApiErr>;
 }
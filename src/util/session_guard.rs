@@ -0,0 +1,89 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use actix_session::SessionExt;
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{web, Error, ResponseError};
+
+use crate::util::TttApiErr;
+use crate::AppState;
+
+/// Rejects every request made with a session of a banned user, or a session created
+/// before user's sessions were invalidated, including websocket upgrades.
+/// Rejected session is purged.
+pub struct SessionGuard;
+
+impl<S, B> Transform<S, ServiceRequest> for SessionGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = SessionGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SessionGuardMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct SessionGuardMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for SessionGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        Box::pin(async move {
+            let session = req.get_session();
+            let data = req.app_data::<web::Data<AppState>>().cloned();
+            if let (Ok(Some(user_id)), Some(data)) = (session.get::<i64>("id"), data) {
+                let db = &data.ttt_db;
+                let epoch = session
+                    .get::<i64>("epoch")
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+                let current_epoch = db
+                    .get_session_epoch(user_id)
+                    .await
+                    .map_err(TttApiErr::from)?;
+                let err = if epoch < current_epoch {
+                    Some(TttApiErr::session_expired())
+                } else {
+                    db.get_active_ban(user_id)
+                        .await
+                        .map_err(TttApiErr::from)?
+                        .map(|ban| TttApiErr::banned(&ban))
+                };
+                if let Some(err) = err {
+                    // Response is returned instead of an error so the session middleware
+                    // still removes the purged session
+                    session.purge();
+                    let res = err.error_response();
+                    return Ok(req.into_response(res).map_into_right_body());
+                }
+            }
+            service
+                .call(req)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
@@ -13,6 +13,24 @@ pub struct Model {
     pub winner: Option<i64>,
     pub end_time: DateTimeWithTimeZone,
     pub start_time: DateTimeWithTimeZone,
+    pub moves: String,
+    pub end_reason: Option<String>,
+    pub user1_elo: i64,
+    pub user2_elo: i64,
+    pub user1_elo_after: i64,
+    pub user2_elo_after: i64,
+    pub user1_elo_delta: i64,
+    pub user2_elo_delta: i64,
+    pub variant: String,
+    pub time_control: String,
+    pub rated: bool,
+    pub rating_system: String,
+    pub user1_deviation: f64,
+    pub user2_deviation: f64,
+    pub user1_k_factor: Option<f64>,
+    pub user2_k_factor: Option<f64>,
+    pub user1_provisional: bool,
+    pub user2_provisional: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
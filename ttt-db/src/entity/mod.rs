@@ -2,7 +2,19 @@
 
 pub mod prelude;
 
+pub mod audit_log;
 pub mod email_verification;
+pub mod friendships;
 pub mod games;
+pub mod messages;
+pub mod password_reset;
+pub mod rating_history;
+pub mod recovery_codes;
+pub mod reports;
+pub mod user_blocks;
+pub mod user_identities;
+pub mod user_mutes;
+pub mod user_ratings;
 pub mod user_stats;
+pub mod user_totp;
 pub mod users;
@@ -1,6 +1,18 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.6.0
 
+pub use super::audit_log::Model as AuditLog;
 pub use super::email_verification::Model as EmailVerification;
+pub use super::friendships::Model as Friendship;
 pub use super::games::Model as Game;
+pub use super::messages::Model as Message;
+pub use super::password_reset::Model as PasswordReset;
+pub use super::rating_history::Model as RatingHistory;
+pub use super::recovery_codes::Model as RecoveryCode;
+pub use super::reports::Model as Report;
+pub use super::user_blocks::Model as UserBlock;
+pub use super::user_identities::Model as UserIdentity;
+pub use super::user_mutes::Model as UserMute;
+pub use super::user_ratings::Model as UserRating;
 pub use super::user_stats::Model as UserStats;
+pub use super::user_totp::Model as UserTotp;
 pub use super::users::Model as User;
@@ -11,7 +11,6 @@ pub struct Model {
     pub wins: i64,
     pub losses: i64,
     pub draws: i64,
-    pub elo: i64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
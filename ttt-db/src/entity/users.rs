@@ -19,7 +19,9 @@ pub struct Model {
     pub email_verified: bool,
     pub is_banned: bool,
     pub ban_ends: Option<DateTimeWithTimeZone>,
+    pub ban_reason: Option<String>,
     pub guest: bool,
+    pub deleted_on: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -3,6 +3,39 @@ mod model;
 mod ttt_db;
 mod util;
 
-pub use crate::model::matchmaking::{Match, PlayerData};
+pub use crate::model::account::{SessionExport, UserExport};
+pub use crate::model::admin::{BanRequest, RatingAdjustment, UserSearch, UserSearchPage};
+pub use crate::model::audit_log::{AuditAction, AuditLogEntry, AuditLogFilter, NewAuditEntry};
+pub use crate::model::bans::Ban;
+pub use crate::model::friends::{Friend, FriendRequest, FriendRequests};
+pub use crate::model::gameplay_stats::{
+    BandStats, EndReasons, FirstMoveStats, GlobalStats, UserGameplayStats,
+};
+pub use crate::model::games::{EndReason, GameRecord};
+pub use crate::model::head_to_head::{HeadToHead, HeadToHeadSummary, Record};
+pub use crate::model::identities::{ExternalIdentity, LinkedIdentity};
+pub use crate::model::match_history::{
+    GameHistoryEntry, GameHistoryFilter, GameHistoryPage, GameResult,
+};
+pub use crate::model::matchmaking::{Match, PlayerData, QueueEntry};
+pub use crate::model::messages::{
+    ChatMessage, Conversation, MessagePage, ReportedMessage, SentMessage,
+};
+pub use crate::model::presence::{Connection, Presence};
+pub use crate::model::profile::{ProfileRating, UserProfile};
+pub use crate::model::rating_history::{
+    HistoryBucket, RatingHistory, RatingHistoryFilter, RatingPoint,
+};
+pub use crate::model::reports::{
+    NewReport, ReportAction, ReportCategory, ReportEntry, ReportFilter, ReportResolution,
+    ReportStatus,
+};
+pub use crate::model::status::{OnlineStatus, UserStatus};
+pub use crate::model::two_factor::{TotpEnrollment, TwoFactorStatus};
 pub use crate::ttt_db::{TttDbConn, TttDbErr};
+pub use crate::util::chat_filter::{
+    ChatFilter, LengthFilter, MessageFilter, ProfanityFilter, UrlFilter,
+};
+pub use crate::util::pool::{Pool, TimeControl, Variant};
+pub use crate::util::rating::{RatingSystem, RATING_SYSTEM};
 pub use crate::util::serializables;
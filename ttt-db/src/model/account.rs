@@ -0,0 +1,223 @@
+use chrono::Utc;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{entity::*, Condition, QueryFilter, QueryOrder, TransactionTrait};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entity::prelude::{
+    Friendship, Game, Message, RatingHistory, Report, User, UserBlock, UserIdentity, UserMute,
+    UserRating, UserStats,
+};
+use crate::entity::{
+    email_verification, friendships, games, messages, password_reset, rating_history,
+    recovery_codes, reports, user_blocks, user_identities, user_mutes, user_ratings, user_stats,
+    user_totp, users,
+};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::pool::Pool;
+
+#[derive(Debug, Serialize)]
+pub struct SessionExport {
+    /// Sessions created before this epoch were invalidated
+    pub epoch: i64,
+    pub online: bool,
+    pub game: Option<Uuid>,
+    pub matchmaking: Option<Pool>,
+}
+
+/// Everything stored about a user.
+#[derive(Debug, Serialize)]
+pub struct UserExport {
+    pub exported_at: DateTimeWithTimeZone,
+    pub profile: User,
+    pub stats: Option<UserStats>,
+    pub ratings: Vec<UserRating>,
+    pub games: Vec<Game>,
+    pub rating_history: Vec<RatingHistory>,
+    pub friendships: Vec<Friendship>,
+    pub blocks: Vec<UserBlock>,
+    pub mutes: Vec<UserMute>,
+    /// Linked accounts of external identity providers
+    pub identities: Vec<UserIdentity>,
+    /// Messages sent or received by the user
+    pub messages: Vec<Message>,
+    /// Reports filed by the user
+    pub reports: Vec<Report>,
+    pub sessions: SessionExport,
+}
+
+impl TttDbConn {
+    pub async fn export_user_data(&self, user_id: i64) -> Result<UserExport, TttDbErr> {
+        let db = &self.db;
+        let profile = self.find_user_by_id(user_id).await?;
+        let stats = user_stats::Entity::find_by_id(user_id).one(db).await?;
+        let ratings = user_ratings::Entity::find()
+            .filter(user_ratings::Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+        let games = games::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(games::Column::User1Id.eq(user_id))
+                    .add(games::Column::User2Id.eq(user_id)),
+            )
+            .order_by_asc(games::Column::StartTime)
+            .all(db)
+            .await?;
+        let rating_history = rating_history::Entity::find()
+            .filter(rating_history::Column::UserId.eq(user_id))
+            .order_by_asc(rating_history::Column::Id)
+            .all(db)
+            .await?;
+        let friendships = friendships::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(friendships::Column::RequesterId.eq(user_id))
+                    .add(friendships::Column::AddresseeId.eq(user_id)),
+            )
+            .all(db)
+            .await?;
+        let blocks = user_blocks::Entity::find()
+            .filter(user_blocks::Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+        let mutes = user_mutes::Entity::find()
+            .filter(user_mutes::Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+        let identities = user_identities::Entity::find()
+            .filter(user_identities::Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+        let messages = messages::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(messages::Column::SenderId.eq(user_id))
+                    .add(messages::Column::RecipientId.eq(user_id)),
+            )
+            .order_by_asc(messages::Column::Id)
+            .all(db)
+            .await?;
+        let reports = reports::Entity::find()
+            .filter(reports::Column::ReporterId.eq(user_id))
+            .order_by_asc(reports::Column::Id)
+            .all(db)
+            .await?;
+        let presence = self.presence();
+        let sessions = SessionExport {
+            epoch: self.get_session_epoch(user_id).await?,
+            online: presence.is_online(user_id).await?,
+            game: presence.get_game(user_id).await?,
+            matchmaking: presence.get_matchmaking(user_id).await?,
+        };
+        Ok(UserExport {
+            exported_at: Utc::now().into(),
+            profile,
+            stats,
+            ratings,
+            games,
+            rating_history,
+            friendships,
+            blocks,
+            mutes,
+            identities,
+            messages,
+            reports,
+            sessions,
+        })
+    }
+    /// Anonymises a user and deletes their personal data.
+    /// Games are kept so opponents' history stays intact, the user is shown under a placeholder.
+    pub async fn delete_user(&self, user_id: i64) -> Result<(), TttDbErr> {
+        if self.get_user_active_game(user_id).await?.is_some() {
+            return Err(TttDbErr::InvalidInput(
+                "Finish your game before deleting your account.".into(),
+            ));
+        }
+        let db = &self.db;
+        let tx = db.begin().await?;
+        let user = users::Entity::find_by_id(user_id).one(&tx).await?;
+        let user = match user {
+            Some(user) if user.deleted_on.is_none() => user,
+            _ => return Err(TttDbErr::UserNotFound),
+        };
+        email_verification::Entity::delete_many()
+            .filter(email_verification::Column::Email.eq(user.email.clone()))
+            .exec(&tx)
+            .await?;
+        password_reset::Entity::delete_many()
+            .filter(password_reset::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+        user_stats::Entity::delete_many()
+            .filter(user_stats::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+        user_ratings::Entity::delete_many()
+            .filter(user_ratings::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+        rating_history::Entity::delete_many()
+            .filter(rating_history::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+        friendships::Entity::delete_many()
+            .filter(
+                Condition::any()
+                    .add(friendships::Column::RequesterId.eq(user_id))
+                    .add(friendships::Column::AddresseeId.eq(user_id)),
+            )
+            .exec(&tx)
+            .await?;
+        user_blocks::Entity::delete_many()
+            .filter(
+                Condition::any()
+                    .add(user_blocks::Column::UserId.eq(user_id))
+                    .add(user_blocks::Column::BlockedId.eq(user_id)),
+            )
+            .exec(&tx)
+            .await?;
+        user_mutes::Entity::delete_many()
+            .filter(
+                Condition::any()
+                    .add(user_mutes::Column::UserId.eq(user_id))
+                    .add(user_mutes::Column::MutedId.eq(user_id)),
+            )
+            .exec(&tx)
+            .await?;
+        user_identities::Entity::delete_many()
+            .filter(user_identities::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+        user_totp::Entity::delete_many()
+            .filter(user_totp::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+        recovery_codes::Entity::delete_many()
+            .filter(recovery_codes::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+        // Reported messages are kept until moderators resolve them
+        messages::Entity::delete_many()
+            .filter(messages::Column::SenderId.eq(user_id))
+            .filter(messages::Column::ReportedBy.is_null())
+            .exec(&tx)
+            .await?;
+        // Placeholder can't be taken by a new user since it is not a valid username
+        let mut user = user.into_active_model();
+        user.username = Set(format!("[deleted-{}]", user_id));
+        user.email = Set(format!("deleted-{}@deleted.invalid", user_id));
+        // Empty string is never a valid password hash so nobody can sign in
+        user.password = Set(String::new());
+        user.is_admin = Set(false);
+        user.email_verified = Set(false);
+        user.ban_reason = Set(None);
+        user.deleted_on = Set(Some(Utc::now().into()));
+        user.update(&tx).await?;
+        tx.commit().await?;
+        self.remove_user_from_mm_queue(user_id).await?;
+        self.invalidate_sessions(user_id).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use sea_orm::entity::*;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{DbBackend, FromQueryResult, Statement};
+use serde::{Deserialize, Serialize};
+
+use crate::entity::user_ratings::Model as UserRating;
+use crate::entity::users::{Entity as User, Model as UserModel};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::pool::Pool;
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 200;
+
+/// Search of users by username or email. Pages are numbered from 0.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct UserSearch {
+    pub query: Option<String>,
+    pub banned: Option<bool>,
+    pub admin: Option<bool>,
+    pub guest: Option<bool>,
+    pub page: u64,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserSearchPage {
+    pub users: Vec<UserModel>,
+    pub total: i64,
+    pub page: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BanRequest {
+    pub reason: String,
+    /// End of the ban, ban is permanent if not set
+    pub ban_ends: Option<DateTimeWithTimeZone>,
+}
+
+/// Manual correction of user's rating in a pool.
+#[derive(Debug, Deserialize)]
+pub struct RatingAdjustment {
+    #[serde(flatten)]
+    pub pool: Pool,
+    pub elo: i64,
+    pub reason: String,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Count {
+    count: i64,
+}
+
+impl TttDbConn {
+    pub async fn search_users(&self, search: UserSearch) -> Result<UserSearchPage, TttDbErr> {
+        let db = &self.db;
+        let limit = search
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let pattern = match search.query {
+            Some(query) if !query.is_empty() => Some(format!(
+                "%{}%",
+                query
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            )),
+            _ => None,
+        };
+        let filter = r#"($1::varchar IS NULL OR username ILIKE $1 OR email ILIKE $1)
+            AND ($2::bool IS NULL OR is_banned = $2)
+            AND ($3::bool IS NULL OR is_admin = $3)
+            AND ($4::bool IS NULL OR guest = $4)"#;
+        let values = vec![
+            pattern.into(),
+            search.banned.into(),
+            search.admin.into(),
+            search.guest.into(),
+        ];
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            &format!("SELECT COUNT(*) AS count FROM users WHERE {}", filter),
+            values.clone(),
+        );
+        let total = Count::find_by_statement(stmt)
+            .one(db)
+            .await?
+            .map_or(0, |c| c.count);
+        let mut values = values;
+        values.push((limit as i64).into());
+        values.push(((search.page * limit) as i64).into());
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            &format!(
+                "SELECT * FROM users WHERE {} ORDER BY user_id LIMIT $5 OFFSET $6",
+                filter
+            ),
+            values,
+        );
+        let users = User::find().from_raw_sql(stmt).all(db).await?;
+        Ok(UserSearchPage {
+            users,
+            total,
+            page: search.page,
+        })
+    }
+    pub async fn set_admin(&self, user_id: i64, admin: bool) -> Result<UserModel, TttDbErr> {
+        let db = &self.db;
+        let user = self.find_user_by_id(user_id).await?;
+        if admin && user.guest {
+            return Err(TttDbErr::GuestNotAllowed);
+        }
+        if admin && !self.has_two_factor(user_id).await? {
+            return Err(TttDbErr::InvalidInput(
+                "User must enable two-factor authentication before being promoted.".into(),
+            ));
+        }
+        let mut user = user.into_active_model();
+        user.is_admin = Set(admin);
+        Ok(user.update(db).await?)
+    }
+    /// Marks user's email as verified without the verification link.
+    pub async fn force_verify_email(&self, user_id: i64) -> Result<UserModel, TttDbErr> {
+        let db = &self.db;
+        let mut user = self.find_user_by_id(user_id).await?.into_active_model();
+        user.email_verified = Set(true);
+        Ok(user.update(db).await?)
+    }
+    /// Sets user's rating in a pool, returns the rating before and after the change.
+    pub async fn adjust_rating(
+        &self,
+        user_id: i64,
+        pool: Pool,
+        elo: i64,
+    ) -> Result<(UserRating, UserRating), TttDbErr> {
+        let db = &self.db;
+        if elo < 0 {
+            return Err(TttDbErr::InvalidInput("Rating can't be negative.".into()));
+        }
+        let before = self.get_pool_rating(user_id, pool).await?;
+        let mut rating = before.clone().into_active_model();
+        rating.elo = Set(elo);
+        let after = rating.update(db).await?;
+        Ok((before, after))
+    }
+}
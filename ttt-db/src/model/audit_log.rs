@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::Utc;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{entity::*, Condition, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entity::audit_log::{self, Entity as AuditLog, Model as AuditLogModel};
+use crate::entity::users::{self, Entity as User};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Ban,
+    Unban,
+    Promote,
+    Demote,
+    VerifyEmail,
+    Adjudicate,
+    RatingAdjustment,
+    ReportResolution,
+    QueueKick,
+    MessageModeration,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ban => "ban",
+            Self::Unban => "unban",
+            Self::Promote => "promote",
+            Self::Demote => "demote",
+            Self::VerifyEmail => "verify_email",
+            Self::Adjudicate => "adjudicate",
+            Self::RatingAdjustment => "rating_adjustment",
+            Self::ReportResolution => "report_resolution",
+            Self::QueueKick => "queue_kick",
+            Self::MessageModeration => "message_moderation",
+        }
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ban" => Ok(Self::Ban),
+            "unban" => Ok(Self::Unban),
+            "promote" => Ok(Self::Promote),
+            "demote" => Ok(Self::Demote),
+            "verify_email" => Ok(Self::VerifyEmail),
+            "adjudicate" => Ok(Self::Adjudicate),
+            "rating_adjustment" => Ok(Self::RatingAdjustment),
+            "report_resolution" => Ok(Self::ReportResolution),
+            "queue_kick" => Ok(Self::QueueKick),
+            "message_moderation" => Ok(Self::MessageModeration),
+            _ => Err(format!("Unknown audit action: {}", s)),
+        }
+    }
+}
+
+/// Admin action to be recorded. Before and after values are stored as JSON.
+#[derive(Debug, Clone)]
+pub struct NewAuditEntry {
+    admin_id: i64,
+    action: AuditAction,
+    target_user_id: Option<i64>,
+    target_game_id: Option<Uuid>,
+    target_report_id: Option<i64>,
+    before: Option<String>,
+    after: Option<String>,
+    reason: Option<String>,
+}
+
+impl NewAuditEntry {
+    pub fn new(admin_id: i64, action: AuditAction) -> Self {
+        Self {
+            admin_id,
+            action,
+            target_user_id: None,
+            target_game_id: None,
+            target_report_id: None,
+            before: None,
+            after: None,
+            reason: None,
+        }
+    }
+    pub fn user(mut self, user_id: i64) -> Self {
+        self.target_user_id = Some(user_id);
+        self
+    }
+    pub fn game(mut self, game_id: Uuid) -> Self {
+        self.target_game_id = Some(game_id);
+        self
+    }
+    pub fn report(mut self, report_id: i64) -> Self {
+        self.target_report_id = Some(report_id);
+        self
+    }
+    pub fn change<B: Serialize, A: Serialize>(mut self, before: &B, after: &A) -> Self {
+        self.before = serde_json::to_string(before).ok();
+        self.after = serde_json::to_string(after).ok();
+        self
+    }
+    pub fn reason(mut self, reason: Option<String>) -> Self {
+        self.reason = reason;
+        self
+    }
+}
+
+/// Filters of the audit log. `before` is the id of the last entry of the previous page.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AuditLogFilter {
+    /// Username of the acting admin
+    pub admin: Option<String>,
+    /// Username of the target user
+    pub target: Option<String>,
+    pub action: Option<AuditAction>,
+    pub game_id: Option<Uuid>,
+    pub report_id: Option<i64>,
+    pub from: Option<DateTimeWithTimeZone>,
+    pub to: Option<DateTimeWithTimeZone>,
+    pub before: Option<i64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub admin: String,
+    /// Stored action, kept as is so entries with actions unknown to this version are still listed
+    pub action: String,
+    pub target_user: Option<String>,
+    pub target_game_id: Option<Uuid>,
+    pub target_report_id: Option<i64>,
+    /// JSON encoded value before the action
+    pub before: Option<String>,
+    /// JSON encoded value after the action
+    pub after: Option<String>,
+    pub reason: Option<String>,
+    pub created_on: DateTimeWithTimeZone,
+}
+
+impl TttDbConn {
+    pub async fn record_audit(&self, entry: NewAuditEntry) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        audit_log::ActiveModel {
+            admin_id: Set(entry.admin_id),
+            action: Set(entry.action.as_str().to_string()),
+            target_user_id: Set(entry.target_user_id),
+            target_game_id: Set(entry.target_game_id),
+            target_report_id: Set(entry.target_report_id),
+            before_value: Set(entry.before),
+            after_value: Set(entry.after),
+            reason: Set(entry.reason),
+            created_on: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        Ok(())
+    }
+    /// Audit log, newest entries first.
+    pub async fn get_audit_log(
+        &self,
+        filter: AuditLogFilter,
+    ) -> Result<Vec<AuditLogEntry>, TttDbErr> {
+        let db = &self.db;
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let mut cond = Condition::all();
+        if let Some(admin) = filter.admin {
+            let admin = self.find_user_by_username(&admin).await?;
+            cond = cond.add(audit_log::Column::AdminId.eq(admin.user_id));
+        }
+        if let Some(target) = filter.target {
+            let target = self.find_user_by_username(&target).await?;
+            cond = cond.add(audit_log::Column::TargetUserId.eq(target.user_id));
+        }
+        if let Some(action) = filter.action {
+            cond = cond.add(audit_log::Column::Action.eq(action.as_str()));
+        }
+        if let Some(game_id) = filter.game_id {
+            cond = cond.add(audit_log::Column::TargetGameId.eq(game_id));
+        }
+        if let Some(report_id) = filter.report_id {
+            cond = cond.add(audit_log::Column::TargetReportId.eq(report_id));
+        }
+        if let Some(from) = filter.from {
+            cond = cond.add(audit_log::Column::CreatedOn.gte(from));
+        }
+        if let Some(to) = filter.to {
+            cond = cond.add(audit_log::Column::CreatedOn.lte(to));
+        }
+        if let Some(before) = filter.before {
+            cond = cond.add(audit_log::Column::Id.lt(before));
+        }
+        let entries = AuditLog::find()
+            .filter(cond)
+            .order_by_desc(audit_log::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await?;
+        self.to_audit_log_entries(entries).await
+    }
+    async fn to_audit_log_entries(
+        &self,
+        entries: Vec<AuditLogModel>,
+    ) -> Result<Vec<AuditLogEntry>, TttDbErr> {
+        let db = &self.db;
+        let mut ids: Vec<i64> = Vec::new();
+        for e in entries.iter() {
+            ids.push(e.admin_id);
+            if let Some(id) = e.target_user_id {
+                ids.push(id);
+            }
+        }
+        let usernames: HashMap<i64, String> = User::find()
+            .filter(users::Column::UserId.is_in(ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|u| (u.user_id, u.username))
+            .collect();
+        let username = |id: &i64| usernames.get(id).cloned().unwrap_or_default();
+        Ok(entries
+            .into_iter()
+            .map(|e| AuditLogEntry {
+                id: e.id,
+                admin: username(&e.admin_id),
+                action: e.action,
+                target_user: e.target_user_id.as_ref().map(username),
+                target_game_id: e.target_game_id,
+                target_report_id: e.target_report_id,
+                before: e.before_value,
+                after: e.after_value,
+                reason: e.reason,
+                created_on: e.created_on,
+            })
+            .collect())
+    }
+}
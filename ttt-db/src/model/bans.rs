@@ -0,0 +1,95 @@
+use chrono::Utc;
+use redis::AsyncCommands;
+use sea_orm::entity::*;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::ActiveValue::Set;
+use serde::{Deserialize, Serialize};
+
+use crate::entity::users::Model as UserModel;
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+/// How long ban status of a user is cached for, in seconds.
+const BAN_CACHE_TTL: usize = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ban {
+    /// End of the ban, ban is permanent if not set
+    pub ban_ends: Option<DateTimeWithTimeZone>,
+    pub reason: Option<String>,
+}
+
+impl Ban {
+    pub fn from_user(user: &UserModel) -> Option<Self> {
+        match user.is_banned {
+            true => Some(Self {
+                ban_ends: user.ban_ends,
+                reason: user.ban_reason.clone(),
+            }),
+            false => None,
+        }
+    }
+    pub fn is_expired(&self) -> bool {
+        match self.ban_ends {
+            Some(ban_ends) => ban_ends < Utc::now(),
+            None => false,
+        }
+    }
+}
+
+impl TttDbConn {
+    /// Bans a user until `ban_ends`, or permanently if it is not set.
+    pub async fn ban_user(
+        &self,
+        user_id: i64,
+        ban_ends: Option<DateTimeWithTimeZone>,
+        reason: Option<String>,
+    ) -> Result<UserModel, TttDbErr> {
+        let db = &self.db;
+        let mut user = self.find_user_by_id(user_id).await?.into_active_model();
+        user.is_banned = Set(true);
+        user.ban_ends = Set(ban_ends);
+        user.ban_reason = Set(reason);
+        let user = user.update(db).await?;
+        self.clear_ban_cache(user_id).await?;
+        Ok(user)
+    }
+    pub async fn unban_user(&self, user_id: i64) -> Result<UserModel, TttDbErr> {
+        let db = &self.db;
+        let mut user = self.find_user_by_id(user_id).await?.into_active_model();
+        user.is_banned = Set(false);
+        user.ban_ends = Set(None);
+        user.ban_reason = Set(None);
+        let user = user.update(db).await?;
+        self.clear_ban_cache(user_id).await?;
+        Ok(user)
+    }
+    /// Returns ban of the user if they are currently banned.
+    /// Expired bans are lifted.
+    pub async fn get_active_ban(&self, user_id: i64) -> Result<Option<Ban>, TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let key = format!("ban_status:{}", user_id);
+        let cached: Option<String> = rdb.get(&key).await?;
+        let ban = match cached.and_then(|s| serde_json::from_str::<Option<Ban>>(&s).ok()) {
+            Some(ban) => ban,
+            None => {
+                let user = self.find_user_by_id(user_id).await?;
+                let ban = Ban::from_user(&user);
+                let value = serde_json::to_string(&ban).unwrap();
+                let _: () = rdb.set_ex(&key, value, BAN_CACHE_TTL).await?;
+                ban
+            }
+        };
+        match ban {
+            Some(ban) if ban.is_expired() => {
+                self.unban_user(user_id).await?;
+                Ok(None)
+            }
+            ban => Ok(ban),
+        }
+    }
+    async fn clear_ban_cache(&self, user_id: i64) -> Result<(), TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let _: () = rdb.del(format!("ban_status:{}", user_id)).await?;
+        Ok(())
+    }
+}
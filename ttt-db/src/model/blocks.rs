@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use chrono::Utc;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{entity::*, Condition, QueryFilter, QueryOrder};
+
+use crate::entity::friendships::{self, Entity as Friendships};
+use crate::entity::user_blocks::{self, Entity as UserBlocks};
+use crate::entity::users::{self, Entity as User};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+impl TttDbConn {
+    /// Blocks a user. Friendship and pending friend requests between users are removed.
+    pub async fn block_user(&self, user_id: i64, username: &str) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let blocked = self.find_user_by_username(username).await?;
+        if blocked.user_id == user_id {
+            return Err(TttDbErr::InvalidInput("You can't block yourself.".into()));
+        }
+        if UserBlocks::find_by_id((user_id, blocked.user_id))
+            .one(db)
+            .await?
+            .is_some()
+        {
+            return Ok(());
+        }
+        user_blocks::ActiveModel {
+            user_id: Set(user_id),
+            blocked_id: Set(blocked.user_id),
+            created_on: Set(Utc::now().into()),
+        }
+        .insert(db)
+        .await?;
+        Friendships::delete_many()
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(friendships::Column::RequesterId.eq(user_id))
+                            .add(friendships::Column::AddresseeId.eq(blocked.user_id)),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(friendships::Column::RequesterId.eq(blocked.user_id))
+                            .add(friendships::Column::AddresseeId.eq(user_id)),
+                    ),
+            )
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+    pub async fn unblock_user(&self, user_id: i64, username: &str) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let blocked = self.find_user_by_username(username).await?;
+        UserBlocks::delete_by_id((user_id, blocked.user_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+    /// Usernames of users blocked by the user.
+    pub async fn get_blocked_users(&self, user_id: i64) -> Result<Vec<String>, TttDbErr> {
+        let db = &self.db;
+        let ids: Vec<i64> = UserBlocks::find()
+            .filter(user_blocks::Column::UserId.eq(user_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|b| b.blocked_id)
+            .collect();
+        let users = User::find()
+            .filter(users::Column::UserId.is_in(ids))
+            .order_by_asc(users::Column::Username)
+            .all(db)
+            .await?;
+        Ok(users.into_iter().map(|u| u.username).collect())
+    }
+    /// Returns true if either user blocked the other one.
+    pub async fn is_blocked(&self, a: i64, b: i64) -> Result<bool, TttDbErr> {
+        let db = &self.db;
+        let res = UserBlocks::find()
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(user_blocks::Column::UserId.eq(a))
+                            .add(user_blocks::Column::BlockedId.eq(b)),
+                    )
+                    .add(
+                        Condition::all()
+                            .add(user_blocks::Column::UserId.eq(b))
+                            .add(user_blocks::Column::BlockedId.eq(a)),
+                    ),
+            )
+            .one(db)
+            .await?;
+        Ok(res.is_some())
+    }
+    /// Ids of users who blocked the user or were blocked by them.
+    pub(crate) async fn get_block_set(&self, user_id: i64) -> Result<HashSet<i64>, TttDbErr> {
+        let db = &self.db;
+        let blocks = UserBlocks::find()
+            .filter(
+                Condition::any()
+                    .add(user_blocks::Column::UserId.eq(user_id))
+                    .add(user_blocks::Column::BlockedId.eq(user_id)),
+            )
+            .all(db)
+            .await?;
+        Ok(blocks
+            .into_iter()
+            .map(|b| match b.user_id == user_id {
+                true => b.blocked_id,
+                false => b.user_id,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{entity::*, Condition, QueryFilter};
+use serde::Serialize;
+
+use crate::entity::friendships::{self, Entity as Friendships, Model as Friendship};
+use crate::entity::users::{self, Entity as User, Model as UserModel};
+use crate::model::status::UserStatus;
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+#[derive(Debug, Serialize)]
+pub struct Friend {
+    pub username: String,
+    pub since: Option<DateTimeWithTimeZone>,
+    pub status: UserStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FriendRequest {
+    pub username: String,
+    pub created_on: DateTimeWithTimeZone,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FriendRequests {
+    pub incoming: Vec<FriendRequest>,
+    pub outgoing: Vec<FriendRequest>,
+}
+
+fn between(a: i64, b: i64) -> Condition {
+    Condition::any()
+        .add(
+            Condition::all()
+                .add(friendships::Column::RequesterId.eq(a))
+                .add(friendships::Column::AddresseeId.eq(b)),
+        )
+        .add(
+            Condition::all()
+                .add(friendships::Column::RequesterId.eq(b))
+                .add(friendships::Column::AddresseeId.eq(a)),
+        )
+}
+
+impl TttDbConn {
+    /// Finds the other user of a friendship, guest accounts can't have friends.
+    async fn find_friend(&self, user_id: i64, username: &str) -> Result<UserModel, TttDbErr> {
+        let user = self.find_user_by_id(user_id).await?;
+        let friend = self.find_user_by_username(username).await?;
+        if user.guest || friend.guest {
+            return Err(TttDbErr::GuestNotAllowed);
+        }
+        if user.user_id == friend.user_id {
+            return Err(TttDbErr::InvalidInput(
+                "You can't be friends with yourself.".into(),
+            ));
+        }
+        if self.is_blocked(user.user_id, friend.user_id).await? {
+            return Err(TttDbErr::UserBlocked);
+        }
+        Ok(friend)
+    }
+    async fn get_friendship(&self, a: i64, b: i64) -> Result<Option<Friendship>, TttDbErr> {
+        let db = &self.db;
+        Ok(Friendships::find().filter(between(a, b)).one(db).await?)
+    }
+    /// Sends a friend request. If the other user already sent one to this user it is accepted instead.
+    /// Returns true if users became friends.
+    pub async fn send_friend_request(
+        &self,
+        user_id: i64,
+        username: &str,
+    ) -> Result<bool, TttDbErr> {
+        let db = &self.db;
+        let friend = self.find_friend(user_id, username).await?;
+        match self.get_friendship(user_id, friend.user_id).await? {
+            Some(f) if !f.accepted && f.requester_id == friend.user_id => {
+                self.accept_friend_request(user_id, username).await?;
+                Ok(true)
+            }
+            Some(_) => Err(TttDbErr::FriendshipExists),
+            None => {
+                friendships::ActiveModel {
+                    requester_id: Set(user_id),
+                    addressee_id: Set(friend.user_id),
+                    accepted: Set(false),
+                    created_on: Set(Utc::now().into()),
+                    accepted_on: Set(None),
+                }
+                .insert(db)
+                .await?;
+                Ok(false)
+            }
+        }
+    }
+    pub async fn accept_friend_request(
+        &self,
+        user_id: i64,
+        username: &str,
+    ) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let friend = self.find_friend(user_id, username).await?;
+        let request = Friendships::find_by_id((friend.user_id, user_id))
+            .one(db)
+            .await?;
+        match request {
+            Some(request) if !request.accepted => {
+                let mut request = request.into_active_model();
+                request.accepted = Set(true);
+                request.accepted_on = Set(Some(Utc::now().into()));
+                request.update(db).await?;
+                Ok(())
+            }
+            _ => Err(TttDbErr::FriendRequestNotFound),
+        }
+    }
+    /// Declines incoming friend request.
+    pub async fn decline_friend_request(
+        &self,
+        user_id: i64,
+        username: &str,
+    ) -> Result<(), TttDbErr> {
+        let friend = self.find_user_by_username(username).await?;
+        self.delete_friend_request(friend.user_id, user_id).await
+    }
+    /// Cancels outgoing friend request.
+    pub async fn cancel_friend_request(
+        &self,
+        user_id: i64,
+        username: &str,
+    ) -> Result<(), TttDbErr> {
+        let friend = self.find_user_by_username(username).await?;
+        self.delete_friend_request(user_id, friend.user_id).await
+    }
+    async fn delete_friend_request(
+        &self,
+        requester_id: i64,
+        addressee_id: i64,
+    ) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let res = Friendships::delete_many()
+            .filter(friendships::Column::RequesterId.eq(requester_id))
+            .filter(friendships::Column::AddresseeId.eq(addressee_id))
+            .filter(friendships::Column::Accepted.eq(false))
+            .exec(db)
+            .await?;
+        match res.rows_affected {
+            0 => Err(TttDbErr::FriendRequestNotFound),
+            _ => Ok(()),
+        }
+    }
+    pub async fn remove_friend(&self, user_id: i64, username: &str) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let friend = self.find_user_by_username(username).await?;
+        let res = Friendships::delete_many()
+            .filter(between(user_id, friend.user_id))
+            .filter(friendships::Column::Accepted.eq(true))
+            .exec(db)
+            .await?;
+        match res.rows_affected {
+            0 => Err(TttDbErr::FriendRequestNotFound),
+            _ => Ok(()),
+        }
+    }
+    pub async fn are_friends(&self, a: i64, b: i64) -> Result<bool, TttDbErr> {
+        Ok(matches!(
+            self.get_friendship(a, b).await?,
+            Some(Friendship { accepted: true, .. })
+        ))
+    }
+    async fn get_friendships(
+        &self,
+        user_id: i64,
+        accepted: bool,
+    ) -> Result<Vec<Friendship>, TttDbErr> {
+        let db = &self.db;
+        let friendships = Friendships::find()
+            .filter(
+                Condition::any()
+                    .add(friendships::Column::RequesterId.eq(user_id))
+                    .add(friendships::Column::AddresseeId.eq(user_id)),
+            )
+            .filter(friendships::Column::Accepted.eq(accepted))
+            .all(db)
+            .await?;
+        Ok(friendships)
+    }
+    /// Maps user ids to usernames.
+    async fn get_usernames(&self, ids: Vec<i64>) -> Result<HashMap<i64, String>, TttDbErr> {
+        let db = &self.db;
+        let users = User::find()
+            .filter(users::Column::UserId.is_in(ids))
+            .all(db)
+            .await?;
+        Ok(users.into_iter().map(|u| (u.user_id, u.username)).collect())
+    }
+    /// Ids of all accepted friends of the user.
+    pub async fn get_friend_ids(&self, user_id: i64) -> Result<Vec<i64>, TttDbErr> {
+        Ok(self
+            .get_friendships(user_id, true)
+            .await?
+            .into_iter()
+            .map(|f| other(&f, user_id))
+            .collect())
+    }
+    pub async fn get_friends(&self, user_id: i64) -> Result<Vec<Friend>, TttDbErr> {
+        let friendships = self.get_friendships(user_id, true).await?;
+        let usernames = self
+            .get_usernames(friendships.iter().map(|f| other(f, user_id)).collect())
+            .await?;
+        let mut friends = Vec::new();
+        for f in friendships {
+            let friend_id = other(&f, user_id);
+            let username = match usernames.get(&friend_id) {
+                Some(username) => username.clone(),
+                None => continue,
+            };
+            friends.push(Friend {
+                username,
+                since: f.accepted_on,
+                status: self.get_user_status(friend_id).await?,
+            });
+        }
+        friends.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(friends)
+    }
+    pub async fn get_friend_requests(&self, user_id: i64) -> Result<FriendRequests, TttDbErr> {
+        let requests = self.get_friendships(user_id, false).await?;
+        let usernames = self
+            .get_usernames(requests.iter().map(|f| other(f, user_id)).collect())
+            .await?;
+        let mut res = FriendRequests {
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        };
+        for f in requests {
+            let username = match usernames.get(&other(&f, user_id)) {
+                Some(username) => username.clone(),
+                None => continue,
+            };
+            let request = FriendRequest {
+                username,
+                created_on: f.created_on,
+            };
+            match f.requester_id == user_id {
+                true => res.outgoing.push(request),
+                false => res.incoming.push(request),
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// The other user of a friendship.
+fn other(f: &Friendship, user_id: i64) -> i64 {
+    match f.requester_id == user_id {
+        true => f.addressee_id,
+        false => f.requester_id,
+    }
+}
@@ -0,0 +1,270 @@
+use redis::AsyncCommands;
+use sea_orm::{DbBackend, FromQueryResult, Statement};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::model::head_to_head::Record;
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+/// How long computed statistics are cached, in seconds.
+const STATS_CACHE_TTL: usize = 600;
+/// Width of rating bands used to group global statistics.
+const RATING_BAND: i64 = 200;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct EndReasons {
+    pub line: i64,
+    pub draw: i64,
+    pub timeout: i64,
+    pub resign: i64,
+}
+
+/// Games started by playing the given field, from the perspective of X.
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+pub struct FirstMoveStats {
+    #[serde(skip)]
+    band: i64,
+    pub field: i64,
+    pub games: i64,
+    pub x_wins: i64,
+    pub o_wins: i64,
+    pub draws: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BandStats {
+    /// Lower bound of average pre-game rating of both players
+    pub rating_from: i64,
+    pub rating_to: i64,
+    pub games: i64,
+    pub x_wins: i64,
+    pub o_wins: i64,
+    pub draws: i64,
+    pub avg_moves: f64,
+    /// Average game length in seconds
+    pub avg_duration: f64,
+    pub end_reasons: EndReasons,
+    pub first_moves: Vec<FirstMoveStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GlobalStats {
+    pub bands: Vec<BandStats>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UserGameplayStats {
+    pub username: String,
+    pub games: i64,
+    pub as_x: Record,
+    pub as_o: Record,
+    pub avg_moves: f64,
+    pub avg_duration: f64,
+    pub end_reasons: EndReasons,
+    /// First moves user made while playing as X
+    pub first_moves: Vec<FirstMoveStats>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct BandRow {
+    band: i64,
+    games: i64,
+    x_wins: i64,
+    o_wins: i64,
+    draws: i64,
+    avg_moves: f64,
+    avg_duration: f64,
+    line: i64,
+    draw: i64,
+    timeout: i64,
+    resign: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct UserRow {
+    as_x: bool,
+    games: i64,
+    wins: i64,
+    losses: i64,
+    draws: i64,
+    avg_moves: f64,
+    avg_duration: f64,
+    line: i64,
+    draw: i64,
+    timeout: i64,
+    resign: i64,
+}
+
+impl TttDbConn {
+    /// Gameplay statistics of all games, grouped by rating band.
+    /// Only games with recorded moves which were played to a result are taken into account,
+    /// aborted and adjudicated games are skipped.
+    pub async fn get_global_stats(&self) -> Result<GlobalStats, TttDbErr> {
+        if let Some(stats) = self.get_cached_stats("stats:global").await? {
+            return Ok(stats);
+        }
+        let db = &self.db;
+        let band = format!(
+            "(((user1_elo + user2_elo) / 2) / {0} * {0})::int8",
+            RATING_BAND
+        );
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            &format!(
+                r#"SELECT {} AS band,
+                    COUNT(*) AS games,
+                    COUNT(*) FILTER (WHERE winner = user1_id) AS x_wins,
+                    COUNT(*) FILTER (WHERE winner = user2_id) AS o_wins,
+                    COUNT(*) FILTER (WHERE winner IS NULL) AS draws,
+                    AVG(length(moves))::float8 AS avg_moves,
+                    AVG(EXTRACT(EPOCH FROM end_time - start_time))::float8 AS avg_duration,
+                    COUNT(*) FILTER (WHERE end_reason = 'line') AS line,
+                    COUNT(*) FILTER (WHERE end_reason = 'draw') AS draw,
+                    COUNT(*) FILTER (WHERE end_reason = 'timeout') AS timeout,
+                    COUNT(*) FILTER (WHERE end_reason = 'resign') AS resign
+                FROM games
+                WHERE end_reason IN ('line', 'draw', 'timeout', 'resign')
+                GROUP BY band
+                ORDER BY band"#,
+                band
+            ),
+            vec![],
+        );
+        let rows = BandRow::find_by_statement(stmt).all(db).await?;
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            &format!(
+                r#"SELECT {} AS band,
+                    substring(moves from 1 for 1)::int8 AS field,
+                    COUNT(*) AS games,
+                    COUNT(*) FILTER (WHERE winner = user1_id) AS x_wins,
+                    COUNT(*) FILTER (WHERE winner = user2_id) AS o_wins,
+                    COUNT(*) FILTER (WHERE winner IS NULL) AS draws
+                FROM games
+                WHERE end_reason IN ('line', 'draw', 'timeout', 'resign') AND moves <> ''
+                GROUP BY band, field
+                ORDER BY band, field"#,
+                band
+            ),
+            vec![],
+        );
+        let first_moves = FirstMoveStats::find_by_statement(stmt).all(db).await?;
+        let bands = rows
+            .into_iter()
+            .map(|row| BandStats {
+                rating_from: row.band,
+                rating_to: row.band + RATING_BAND,
+                games: row.games,
+                x_wins: row.x_wins,
+                o_wins: row.o_wins,
+                draws: row.draws,
+                avg_moves: row.avg_moves,
+                avg_duration: row.avg_duration,
+                end_reasons: EndReasons {
+                    line: row.line,
+                    draw: row.draw,
+                    timeout: row.timeout,
+                    resign: row.resign,
+                },
+                first_moves: first_moves
+                    .iter()
+                    .filter(|m| m.band == row.band)
+                    .cloned()
+                    .collect(),
+            })
+            .collect();
+        let stats = GlobalStats { bands };
+        self.cache_stats("stats:global", &stats).await?;
+        Ok(stats)
+    }
+    pub async fn get_user_gameplay_stats(
+        &self,
+        username: &str,
+    ) -> Result<UserGameplayStats, TttDbErr> {
+        let user = self.find_user_by_username(username).await?;
+        let key = format!("stats:user:{}", user.user_id);
+        if let Some(stats) = self.get_cached_stats(&key).await? {
+            return Ok(stats);
+        }
+        let db = &self.db;
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"SELECT user1_id = $1 AS as_x,
+                COUNT(*) AS games,
+                COUNT(*) FILTER (WHERE winner = $1) AS wins,
+                COUNT(*) FILTER (WHERE winner IS NOT NULL AND winner <> $1) AS losses,
+                COUNT(*) FILTER (WHERE winner IS NULL) AS draws,
+                AVG(length(moves))::float8 AS avg_moves,
+                AVG(EXTRACT(EPOCH FROM end_time - start_time))::float8 AS avg_duration,
+                COUNT(*) FILTER (WHERE end_reason = 'line') AS line,
+                COUNT(*) FILTER (WHERE end_reason = 'draw') AS draw,
+                COUNT(*) FILTER (WHERE end_reason = 'timeout') AS timeout,
+                COUNT(*) FILTER (WHERE end_reason = 'resign') AS resign
+            FROM games
+            WHERE (user1_id = $1 OR user2_id = $1)
+                AND end_reason IN ('line', 'draw', 'timeout', 'resign')
+            GROUP BY as_x"#,
+            vec![user.user_id.into()],
+        );
+        let rows = UserRow::find_by_statement(stmt).all(db).await?;
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"SELECT 0::int8 AS band,
+                substring(moves from 1 for 1)::int8 AS field,
+                COUNT(*) AS games,
+                COUNT(*) FILTER (WHERE winner = user1_id) AS x_wins,
+                COUNT(*) FILTER (WHERE winner = user2_id) AS o_wins,
+                COUNT(*) FILTER (WHERE winner IS NULL) AS draws
+            FROM games
+            WHERE user1_id = $1 AND end_reason IN ('line', 'draw', 'timeout', 'resign') AND moves <> ''
+            GROUP BY field
+            ORDER BY field"#,
+            vec![user.user_id.into()],
+        );
+        let first_moves = FirstMoveStats::find_by_statement(stmt).all(db).await?;
+        let mut stats = UserGameplayStats {
+            username: user.username,
+            first_moves,
+            ..Default::default()
+        };
+        let mut moves = 0.0;
+        let mut duration = 0.0;
+        for row in rows {
+            let record = Record {
+                wins: row.wins,
+                losses: row.losses,
+                draws: row.draws,
+            };
+            match row.as_x {
+                true => stats.as_x = record,
+                false => stats.as_o = record,
+            }
+            stats.games += row.games;
+            moves += row.avg_moves * row.games as f64;
+            duration += row.avg_duration * row.games as f64;
+            stats.end_reasons.line += row.line;
+            stats.end_reasons.draw += row.draw;
+            stats.end_reasons.timeout += row.timeout;
+            stats.end_reasons.resign += row.resign;
+        }
+        if stats.games > 0 {
+            stats.avg_moves = moves / stats.games as f64;
+            stats.avg_duration = duration / stats.games as f64;
+        }
+        self.cache_stats(&key, &stats).await?;
+        Ok(stats)
+    }
+    async fn get_cached_stats<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let res: Option<String> = rdb.get(key).await?;
+        Ok(res.and_then(|res| serde_json::from_str(&res).ok()))
+    }
+    async fn cache_stats<T: Serialize>(&self, key: &str, stats: &T) -> Result<(), TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let stats = serde_json::to_string(stats).unwrap();
+        let _: () = rdb.set_ex(key, stats, STATS_CACHE_TTL).await?;
+        Ok(())
+    }
+}
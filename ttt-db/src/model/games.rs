@@ -1,13 +1,61 @@
-use crate::entity::{games, user_stats};
+use crate::entity::{games, rating_history, user_ratings, user_stats};
+use crate::util::pool::Pool;
+use crate::util::rating::{rate, Rating, RATING_SYSTEM};
 use crate::{TttDbConn, TttDbErr};
 use redis::AsyncCommands;
 use sea_orm::entity::*;
 use sea_orm::prelude::DateTimeWithTimeZone;
 use sea_orm::ActiveValue::Set;
+use sea_orm::TransactionTrait;
+use serde::{Deserialize, Serialize};
 use skillratings::Outcomes;
-use skillratings::elo::{EloConfig, EloRating, elo};
 use uuid::Uuid;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EndReason {
+    /// One of the players completed a line
+    Line,
+    /// Board filled up without a completed line
+    Draw,
+    Timeout,
+    Resign,
+    /// Ended by an admin without a result, game is not rated
+    Aborted,
+    /// Ended by an admin with the result they decided
+    Adjudicated,
+}
+
+impl EndReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Line => "line",
+            Self::Draw => "draw",
+            Self::Timeout => "timeout",
+            Self::Resign => "resign",
+            Self::Aborted => "aborted",
+            Self::Adjudicated => "adjudicated",
+        }
+    }
+}
+
+/// Finished game as reported by the game server.
+/// First player always plays as X.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub game_id: Uuid,
+    pub pool: Pool,
+    pub rated: bool,
+    pub user1_id: i64,
+    pub user2_id: i64,
+    pub winner: Option<i64>,
+    /// Indices of played fields in order they were played
+    pub moves: Vec<usize>,
+    pub end_reason: EndReason,
+    pub start_time: DateTimeWithTimeZone,
+    pub end_time: DateTimeWithTimeZone,
+}
+
 impl TttDbConn {
     pub async fn create_active_game(
         &self,
@@ -15,18 +63,43 @@ impl TttDbConn {
         players: (i64, i64),
     ) -> Result<(), TttDbErr> {
         let mut rdb = self.rdb.get_async_connection().await?;
-        rdb.hset_multiple(
-            format!("active_game:{}", game_id),
-            &[("player1_id", players.0), ("player2_id", players.1)],
-        )
-        .await?;
+        let _: () = rdb
+            .hset_multiple(
+                format!("active_game:{}", game_id),
+                &[("player1_id", players.0), ("player2_id", players.1)],
+            )
+            .await?;
+        let _: () = rdb
+            .set(
+                format!("user_active_game:{}", players.0),
+                game_id.to_string(),
+            )
+            .await?;
+        let _: () = rdb
+            .set(
+                format!("user_active_game:{}", players.1),
+                game_id.to_string(),
+            )
+            .await?;
         Ok(())
     }
     pub async fn delete_active_game(&self, game_id: Uuid) -> Result<(), TttDbErr> {
         let mut rdb = self.rdb.get_async_connection().await?;
-        rdb.del(format!("active_game:{}", game_id)).await?;
+        let players = rdb
+            .hgetall::<String, Vec<(String, i64)>>(format!("active_game:{}", game_id))
+            .await?;
+        for (_, user_id) in players {
+            let _: () = rdb.del(format!("user_active_game:{}", user_id)).await?;
+        }
+        let _: () = rdb.del(format!("active_game:{}", game_id)).await?;
         Ok(())
     }
+    /// Returns id of the game user is currently playing, if any.
+    pub async fn get_user_active_game(&self, user_id: i64) -> Result<Option<Uuid>, TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let game_id: Option<String> = rdb.get(format!("user_active_game:{}", user_id)).await?;
+        Ok(game_id.and_then(|id| Uuid::parse_str(&id).ok()))
+    }
     pub async fn check_user_in_active_game(
         &self,
         game_id: Uuid,
@@ -43,35 +116,21 @@ impl TttDbConn {
         }
         Ok(false)
     }
-    pub async fn record_game(
-        &self,
-        game_id: Uuid,
-        user1_id: i64,
-        user2_id: i64,
-        winner: Option<i64>,
-        start_time: DateTimeWithTimeZone,
-        end_time: DateTimeWithTimeZone,
-    ) -> Result<(), TttDbErr> {
+    pub async fn record_game(&self, game: GameRecord) -> Result<(), TttDbErr> {
         let db = &self.db;
-        self.delete_active_game(game_id).await?;
-        games::ActiveModel {
-            game_id: Set(game_id),
-            user1_id: Set(user1_id),
-            user2_id: Set(user2_id),
-            winner: Set(winner),
-            start_time: Set(start_time),
-            end_time: Set(end_time),
-        }
-        .insert(db)
-        .await?;
-        let p1_data = self.get_user_data(user1_id).await?;
-        let p2_data = self.get_user_data(user2_id).await?;
-        let p1_elo = EloRating {
-            rating: p1_data.elo as f64,
-        };
-        let p2_elo = EloRating {
-            rating: p2_data.elo as f64,
-        };
+        let GameRecord {
+            game_id,
+            pool,
+            rated,
+            user1_id,
+            user2_id,
+            winner,
+            moves,
+            end_reason,
+            start_time,
+            end_time,
+        } = game;
+        let moves: String = moves.iter().map(|i| i.to_string()).collect();
         let outcome = match winner {
             Some(id) => {
                 if id == user1_id {
@@ -82,32 +141,153 @@ impl TttDbConn {
             }
             None => Outcomes::DRAW,
         };
-        let config = EloConfig::new();
-        let p1_games = match outcome {
-            Outcomes::WIN => (p1_data.wins + 1, p1_data.draws, p1_data.losses),
-            Outcomes::DRAW => (p1_data.wins, p1_data.draws + 1, p1_data.losses),
-            Outcomes::LOSS => (p1_data.wins, p1_data.draws, p1_data.losses + 1),
-        };
-        let p2_games = match outcome {
-            Outcomes::WIN => (p2_data.wins, p2_data.draws, p2_data.losses + 1),
-            Outcomes::DRAW => (p2_data.wins, p2_data.draws + 1, p2_data.losses),
-            Outcomes::LOSS => (p2_data.wins + 1, p2_data.draws, p2_data.losses),
+        let p1_data = self.get_pool_rating(user1_id, pool).await?;
+        let p2_data = self.get_pool_rating(user2_id, pool).await?;
+        let p1_rating = Rating {
+            rating: p1_data.elo as f64,
+            deviation: p1_data.deviation,
+            volatility: p1_data.volatility,
+            games_played: p1_data.games_played,
+        }
+        .decay(p1_data.last_game);
+        let p2_rating = Rating {
+            rating: p2_data.elo as f64,
+            deviation: p2_data.deviation,
+            volatility: p2_data.volatility,
+            games_played: p2_data.games_played,
+        }
+        .decay(p2_data.last_game);
+        let (p1_new, p2_new) = match rated {
+            true => rate(p1_rating, p2_rating, &outcome),
+            false => (p1_rating, p2_rating),
         };
-        let (p1_elo, p2_elo) = elo(&p1_elo, &p2_elo, &outcome, &config);
-        let p1_elo = p1_elo.rating as i64;
-        let p2_elo = p2_elo.rating as i64;
-        let mut p1 = p1_data.into_active_model();
-        let mut p2 = p2_data.into_active_model();
-        p1.set(user_stats::Column::Elo, Value::BigInt(Some(p1_elo)));
+        let p1_elo = (p1_data.elo, p1_new.rating as i64);
+        let p2_elo = (p2_data.elo, p2_new.rating as i64);
+        let tx = db.begin().await?;
+        games::ActiveModel {
+            game_id: Set(game_id),
+            user1_id: Set(user1_id),
+            user2_id: Set(user2_id),
+            winner: Set(winner),
+            start_time: Set(start_time),
+            end_time: Set(end_time),
+            moves: Set(moves),
+            end_reason: Set(Some(end_reason.as_str().to_string())),
+            user1_elo: Set(p1_elo.0),
+            user2_elo: Set(p2_elo.0),
+            user1_elo_after: Set(p1_elo.1),
+            user2_elo_after: Set(p2_elo.1),
+            user1_elo_delta: Set(p1_elo.1 - p1_elo.0),
+            user2_elo_delta: Set(p2_elo.1 - p2_elo.0),
+            variant: Set(pool.variant.as_str().to_string()),
+            time_control: Set(pool.time_control.as_str().to_string()),
+            rated: Set(rated),
+            rating_system: Set(RATING_SYSTEM.as_str().to_string()),
+            user1_deviation: Set(p1_rating.deviation),
+            user2_deviation: Set(p2_rating.deviation),
+            user1_k_factor: Set(p1_rating.applied_k_factor()),
+            user2_k_factor: Set(p2_rating.applied_k_factor()),
+            user1_provisional: Set(p1_rating.is_provisional()),
+            user2_provisional: Set(p2_rating.is_provisional()),
+        }
+        .insert(&tx)
+        .await?;
+        // Aborted games are kept in history but don't count towards any record
+        if end_reason == EndReason::Aborted {
+            return Ok(());
+        }
+        let p1_stats = self.get_user_stats(user1_id).await?;
+        let p2_stats = self.get_user_stats(user2_id).await?;
+        let p1_games = count_game(&outcome, (p1_stats.wins, p1_stats.draws, p1_stats.losses));
+        let p2_games = count_game(&outcome, (p2_stats.losses, p2_stats.draws, p2_stats.wins));
+        let mut p1 = p1_stats.into_active_model();
+        let mut p2 = p2_stats.into_active_model();
         p1.set(user_stats::Column::Wins, Value::BigInt(Some(p1_games.0)));
         p1.set(user_stats::Column::Draws, Value::BigInt(Some(p1_games.1)));
         p1.set(user_stats::Column::Losses, Value::BigInt(Some(p1_games.2)));
-        p1.update(db).await?;
-        p2.set(user_stats::Column::Elo, Value::BigInt(Some(p2_elo)));
-        p2.set(user_stats::Column::Wins, Value::BigInt(Some(p2_games.0)));
+        p1.update(&tx).await?;
+        p2.set(user_stats::Column::Wins, Value::BigInt(Some(p2_games.2)));
         p2.set(user_stats::Column::Draws, Value::BigInt(Some(p2_games.1)));
-        p2.set(user_stats::Column::Losses, Value::BigInt(Some(p2_games.2)));
-        p2.update(db).await?;
+        p2.set(user_stats::Column::Losses, Value::BigInt(Some(p2_games.0)));
+        p2.update(&tx).await?;
+        if !rated {
+            tx.commit().await?;
+            self.delete_active_game(game_id).await?;
+            return Ok(());
+        }
+        let p1_games = count_game(&outcome, (p1_data.wins, p1_data.draws, p1_data.losses));
+        let p2_games = count_game(&outcome, (p2_data.losses, p2_data.draws, p2_data.wins));
+        let mut p1 = p1_data.into_active_model();
+        let mut p2 = p2_data.into_active_model();
+        p1.set(user_ratings::Column::Elo, Value::BigInt(Some(p1_elo.1)));
+        p1.set(
+            user_ratings::Column::Deviation,
+            Value::Double(Some(p1_new.deviation)),
+        );
+        p1.set(
+            user_ratings::Column::Volatility,
+            Value::Double(Some(p1_new.volatility)),
+        );
+        p1.set(
+            user_ratings::Column::GamesPlayed,
+            Value::BigInt(Some(p1_new.games_played)),
+        );
+        p1.set(user_ratings::Column::Wins, Value::BigInt(Some(p1_games.0)));
+        p1.set(user_ratings::Column::Draws, Value::BigInt(Some(p1_games.1)));
+        p1.set(
+            user_ratings::Column::Losses,
+            Value::BigInt(Some(p1_games.2)),
+        );
+        p1.last_game = Set(Some(end_time));
+        p1.update(&tx).await?;
+        p2.set(user_ratings::Column::Elo, Value::BigInt(Some(p2_elo.1)));
+        p2.set(
+            user_ratings::Column::Deviation,
+            Value::Double(Some(p2_new.deviation)),
+        );
+        p2.set(
+            user_ratings::Column::Volatility,
+            Value::Double(Some(p2_new.volatility)),
+        );
+        p2.set(
+            user_ratings::Column::GamesPlayed,
+            Value::BigInt(Some(p2_new.games_played)),
+        );
+        p2.set(user_ratings::Column::Wins, Value::BigInt(Some(p2_games.2)));
+        p2.set(user_ratings::Column::Draws, Value::BigInt(Some(p2_games.1)));
+        p2.set(
+            user_ratings::Column::Losses,
+            Value::BigInt(Some(p2_games.0)),
+        );
+        p2.last_game = Set(Some(end_time));
+        p2.update(&tx).await?;
+        for (user_id, elo) in [(user1_id, p1_elo), (user2_id, p2_elo)] {
+            rating_history::ActiveModel {
+                user_id: Set(user_id),
+                game_id: Set(game_id),
+                variant: Set(pool.variant.as_str().to_string()),
+                time_control: Set(pool.time_control.as_str().to_string()),
+                elo_before: Set(elo.0),
+                elo_after: Set(elo.1),
+                recorded_at: Set(end_time),
+                ..Default::default()
+            }
+            .insert(&tx)
+            .await?;
+        }
+        tx.commit().await?;
+        // Active game is removed only once its result is stored
+        self.delete_active_game(game_id).await?;
         Ok(())
     }
 }
+
+/// Adds game outcome to `(wins, draws, losses)` counts of the first player.
+/// To count the game for the second player pass `(losses, draws, wins)` instead.
+fn count_game(outcome: &Outcomes, games: (i64, i64, i64)) -> (i64, i64, i64) {
+    match outcome {
+        Outcomes::WIN => (games.0 + 1, games.1, games.2),
+        Outcomes::DRAW => (games.0, games.1 + 1, games.2),
+        Outcomes::LOSS => (games.0, games.1, games.2 + 1),
+    }
+}
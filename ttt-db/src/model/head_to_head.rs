@@ -0,0 +1,157 @@
+use sea_orm::{DbBackend, FromQueryResult, Statement};
+use serde::{Deserialize, Serialize};
+
+use crate::model::match_history::{GameHistoryEntry, GameHistoryFilter};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+const H2H_LAST_GAMES: u64 = 10;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Record {
+    pub wins: i64,
+    pub losses: i64,
+    pub draws: i64,
+}
+
+impl Record {
+    fn add(self, other: Record) -> Self {
+        Self {
+            wins: self.wins + other.wins,
+            losses: self.losses + other.losses,
+            draws: self.draws + other.draws,
+        }
+    }
+    fn flip(self) -> Self {
+        Self {
+            wins: self.losses,
+            losses: self.wins,
+            draws: self.draws,
+        }
+    }
+}
+
+/// Short head-to-head summary sent to players before the game starts.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct HeadToHeadSummary {
+    pub record: Record,
+    /// Rating points won from the opponent in rated games
+    pub net_rating: i64,
+}
+
+impl HeadToHeadSummary {
+    /// Returns the same summary seen from the opponent's side.
+    pub fn flip(&self) -> Self {
+        Self {
+            record: self.record.flip(),
+            net_rating: -self.net_rating,
+        }
+    }
+}
+
+/// Head-to-head statistics seen from the perspective of `player`.
+#[derive(Debug, Serialize)]
+pub struct HeadToHead {
+    pub player: String,
+    pub opponent: String,
+    pub total: Record,
+    pub as_x: Record,
+    pub as_o: Record,
+    pub net_rating: i64,
+    pub last_games: Vec<GameHistoryEntry>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct H2hRow {
+    as_x: bool,
+    wins: i64,
+    losses: i64,
+    draws: i64,
+    net_rating: i64,
+}
+
+impl TttDbConn {
+    pub async fn get_head_to_head(
+        &self,
+        username: &str,
+        opp_username: &str,
+    ) -> Result<HeadToHead, TttDbErr> {
+        let player = self.find_user_by_username(username).await?;
+        let opponent = self.find_user_by_username(opp_username).await?;
+        let rows = self.h2h_rows(player.user_id, opponent.user_id).await?;
+        let mut as_x = Record::default();
+        let mut as_o = Record::default();
+        let mut net_rating = 0;
+        for row in rows {
+            let record = Record {
+                wins: row.wins,
+                losses: row.losses,
+                draws: row.draws,
+            };
+            match row.as_x {
+                true => as_x = record,
+                false => as_o = record,
+            }
+            net_rating += row.net_rating;
+        }
+        let last_games = self
+            .get_match_history(
+                player.user_id,
+                GameHistoryFilter {
+                    limit: Some(H2H_LAST_GAMES),
+                    opponent: Some(opponent.username.clone()),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .games;
+        Ok(HeadToHead {
+            player: player.username,
+            opponent: opponent.username,
+            total: as_x.add(as_o),
+            as_x,
+            as_o,
+            net_rating,
+            last_games,
+        })
+    }
+    /// Head-to-head summary seen from the perspective of the first player.
+    pub async fn get_head_to_head_summary(
+        &self,
+        user_id: i64,
+        opp_id: i64,
+    ) -> Result<HeadToHeadSummary, TttDbErr> {
+        let rows = self.h2h_rows(user_id, opp_id).await?;
+        let mut summary = HeadToHeadSummary::default();
+        for row in rows {
+            summary.record = summary.record.add(Record {
+                wins: row.wins,
+                losses: row.losses,
+                draws: row.draws,
+            });
+            summary.net_rating += row.net_rating;
+        }
+        Ok(summary)
+    }
+    /// Aggregates all games between two players, grouped by the colour of the first one.
+    /// First player in a game (`user1_id`) always plays as X. Aborted games have no result
+    /// and are skipped.
+    async fn h2h_rows(&self, user_id: i64, opp_id: i64) -> Result<Vec<H2hRow>, TttDbErr> {
+        let db = &self.db;
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"SELECT user1_id = $1 AS as_x,
+                COUNT(*) FILTER (WHERE winner = $1) AS wins,
+                COUNT(*) FILTER (WHERE winner = $2) AS losses,
+                COUNT(*) FILTER (WHERE winner IS NULL) AS draws,
+                COALESCE(SUM(CASE WHEN user1_id = $1 THEN user1_elo_delta ELSE user2_elo_delta END)
+                    FILTER (WHERE rated), 0)::int8 AS net_rating
+            FROM games
+            WHERE ((user1_id = $1 AND user2_id = $2) OR (user1_id = $2 AND user2_id = $1))
+                AND end_reason IS DISTINCT FROM 'aborted'
+            GROUP BY as_x"#,
+            vec![user_id.into(), opp_id.into()],
+        );
+        let res = H2hRow::find_by_statement(stmt).all(db).await?;
+        Ok(res)
+    }
+}
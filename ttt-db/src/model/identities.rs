@@ -0,0 +1,210 @@
+use chrono::Utc;
+use convert_case::{Case, Casing};
+use log::warn;
+use petname::Petnames;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{entity::*, DbErr, QueryFilter, QueryOrder, RuntimeErr, TransactionTrait};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entity::user_identities::{self, Entity as UserIdentity};
+use crate::entity::user_stats;
+use crate::entity::users::{self, Entity as User, Model as UserModel};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::validators::is_valid_email;
+
+/// Account of a user at an external OpenID Connect provider.
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub provider: String,
+    /// Subject id of the user, unique per provider
+    pub subject: String,
+    /// Email of the user, only set if the provider verified it
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinkedIdentity {
+    pub provider: String,
+    pub linked_on: DateTimeWithTimeZone,
+}
+
+fn map_insert_err(err: DbErr) -> TttDbErr {
+    match err {
+        DbErr::Query(RuntimeErr::SqlxError(s)) => {
+            if let Some(err) = s.as_database_error() {
+                return match err.constraint() {
+                    Some("users_unique_username") => TttDbErr::UsernameConfilct,
+                    Some("users_unique_email") => TttDbErr::EmailConflict,
+                    Some("user_identities_pk") => TttDbErr::IdentityLinked,
+                    Some("user_identities_unique_user") => TttDbErr::InvalidInput(
+                        "Your account is already linked to this provider.".into(),
+                    ),
+                    _ => TttDbErr::Unhandled,
+                };
+            }
+            warn!("{}", s);
+            TttDbErr::Unhandled
+        }
+        _ => TttDbErr::DbErr(err),
+    }
+}
+
+impl TttDbConn {
+    pub async fn find_user_by_identity(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Option<UserModel>, TttDbErr> {
+        let db = &self.db;
+        let identity = UserIdentity::find_by_id((provider.to_string(), subject.to_string()))
+            .one(db)
+            .await?;
+        let identity = match identity {
+            Some(identity) => identity,
+            None => return Ok(None),
+        };
+        let user = User::find_by_id(identity.user_id)
+            .filter(users::Column::DeletedOn.is_null())
+            .one(db)
+            .await?;
+        Ok(user)
+    }
+    /// Signs in with an external identity. First sign in creates a new account with
+    /// a generated username, which can be changed later.
+    pub async fn sign_in_with_identity(
+        &self,
+        identity: ExternalIdentity,
+    ) -> Result<UserModel, TttDbErr> {
+        if let Some(user) = self
+            .find_user_by_identity(&identity.provider, &identity.subject)
+            .await?
+        {
+            return Ok(user);
+        }
+        let db = &self.db;
+        let username = Petnames::default()
+            .generate_one(3, "_")
+            .to_case(Case::Pascal);
+        // Verified email of the provider is used unless it belongs to another account,
+        // accounts are never linked just because their emails match
+        let email = match identity.email {
+            Some(email) if is_valid_email(&email) => {
+                let taken = User::find()
+                    .filter(users::Column::Email.eq(email.clone()))
+                    .one(db)
+                    .await?
+                    .is_some();
+                if taken {
+                    None
+                } else {
+                    Some(email)
+                }
+            }
+            _ => None,
+        };
+        let email_verified = email.is_some();
+        let email = email.unwrap_or(format!("{}@oidc.invalid", Uuid::new_v4().simple()));
+        let tx = db.begin().await?;
+        // Empty string is never a valid password hash, password can be set with a password reset
+        let user = users::ActiveModel {
+            email: Set(email),
+            password: Set(String::new()),
+            username: Set(username),
+            email_verified: Set(email_verified),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await
+        .map_err(map_insert_err)?;
+        user_stats::ActiveModel {
+            user_id: Set(user.user_id),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+        user_identities::ActiveModel {
+            provider: Set(identity.provider),
+            subject: Set(identity.subject),
+            user_id: Set(user.user_id),
+            created_on: Set(Utc::now().into()),
+        }
+        .insert(&tx)
+        .await
+        .map_err(map_insert_err)?;
+        tx.commit().await?;
+        Ok(user)
+    }
+    /// Links an external identity to an existing account.
+    pub async fn link_identity(
+        &self,
+        user_id: i64,
+        identity: ExternalIdentity,
+    ) -> Result<(), TttDbErr> {
+        let user = self.find_user_by_id(user_id).await?;
+        if user.guest {
+            return Err(TttDbErr::GuestNotAllowed);
+        }
+        if let Some(linked) = self
+            .find_user_by_identity(&identity.provider, &identity.subject)
+            .await?
+        {
+            if linked.user_id == user_id {
+                return Ok(());
+            }
+            return Err(TttDbErr::IdentityLinked);
+        }
+        let db = &self.db;
+        user_identities::ActiveModel {
+            provider: Set(identity.provider),
+            subject: Set(identity.subject),
+            user_id: Set(user_id),
+            created_on: Set(Utc::now().into()),
+        }
+        .insert(db)
+        .await
+        .map_err(map_insert_err)?;
+        Ok(())
+    }
+    pub async fn get_identities(&self, user_id: i64) -> Result<Vec<LinkedIdentity>, TttDbErr> {
+        let db = &self.db;
+        let identities = UserIdentity::find()
+            .filter(user_identities::Column::UserId.eq(user_id))
+            .order_by_asc(user_identities::Column::CreatedOn)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|i| LinkedIdentity {
+                provider: i.provider,
+                linked_on: i.created_on,
+            })
+            .collect();
+        Ok(identities)
+    }
+    /// Unlinks an external identity, unless it is the only way left to sign in.
+    pub async fn unlink_identity(&self, user_id: i64, provider: &str) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let user = self.find_user_by_id(user_id).await?;
+        let identities = UserIdentity::find()
+            .filter(user_identities::Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+        let identity = identities.iter().find(|i| i.provider == provider);
+        let identity = match identity {
+            Some(identity) => identity.clone(),
+            None => {
+                return Err(TttDbErr::InvalidInput(
+                    "Your account is not linked to this provider.".into(),
+                ))
+            }
+        };
+        if user.password.is_empty() && identities.len() == 1 {
+            return Err(TttDbErr::InvalidInput(
+                "Set a password before unlinking your only sign in method.".into(),
+            ));
+        }
+        identity.delete(db).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use chrono::{TimeZone, Utc};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entity::{games, users};
+use crate::model::games::EndReason;
+use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::pool::{TimeControl, Variant};
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameResult {
+    Win,
+    Loss,
+    Draw,
+    /// Ended by an admin without a result
+    Aborted,
+}
+
+/// Single game seen from the perspective of one of its players.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameHistoryEntry {
+    pub game_id: Uuid,
+    pub opponent: String,
+    pub result: GameResult,
+    pub variant: String,
+    pub time_control: String,
+    pub elo_before: i64,
+    pub elo_after: i64,
+    pub elo_change: i64,
+    pub opp_elo_before: i64,
+    pub opp_elo_after: i64,
+    pub rated: bool,
+    /// Game duration in seconds
+    pub duration: i64,
+    pub start_time: DateTimeWithTimeZone,
+    pub end_time: DateTimeWithTimeZone,
+}
+
+/// Filters and pagination of match history.
+/// `cursor` is the `next_cursor` returned with the previous page.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GameHistoryFilter {
+    pub cursor: Option<String>,
+    pub limit: Option<u64>,
+    pub result: Option<GameResult>,
+    pub opponent: Option<String>,
+    pub from: Option<DateTimeWithTimeZone>,
+    pub to: Option<DateTimeWithTimeZone>,
+    pub variant: Option<Variant>,
+    pub time_control: Option<TimeControl>,
+    pub rated: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameHistoryPage {
+    pub games: Vec<GameHistoryEntry>,
+    pub next_cursor: Option<String>,
+}
+
+/// Position in match history, games are ordered by end time and then by game id.
+struct Cursor {
+    end_time: DateTimeWithTimeZone,
+    game_id: Uuid,
+}
+
+impl Cursor {
+    fn encode(game: &GameHistoryEntry) -> String {
+        format!(
+            "{}_{}_{}",
+            game.end_time.timestamp(),
+            game.end_time.timestamp_subsec_nanos(),
+            game.game_id
+        )
+    }
+    fn decode(cursor: &str) -> Result<Self, TttDbErr> {
+        let err = || TttDbErr::InvalidInput("Invalid cursor.".into());
+        let mut parts = cursor.splitn(3, '_');
+        let secs = parts
+            .next()
+            .and_then(|s| s.parse::<i64>().ok())
+            .ok_or_else(err)?;
+        let nanos = parts
+            .next()
+            .and_then(|s| s.parse::<u32>().ok())
+            .ok_or_else(err)?;
+        let game_id = parts
+            .next()
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .ok_or_else(err)?;
+        let end_time = Utc.timestamp_opt(secs, nanos).single().ok_or_else(err)?;
+        Ok(Self {
+            end_time: end_time.into(),
+            game_id,
+        })
+    }
+}
+
+impl GameHistoryEntry {
+    pub(crate) fn new(game: games::Model, user_id: i64, opponent: String) -> Self {
+        let aborted = game.end_reason.as_deref() == Some(EndReason::Aborted.as_str());
+        let result = match game.winner {
+            None if aborted => GameResult::Aborted,
+            None => GameResult::Draw,
+            Some(winner) if winner == user_id => GameResult::Win,
+            Some(_) => GameResult::Loss,
+        };
+        let (elo_before, elo_after, elo_change, opp_elo_before, opp_elo_after) =
+            if game.user1_id == user_id {
+                (
+                    game.user1_elo,
+                    game.user1_elo_after,
+                    game.user1_elo_delta,
+                    game.user2_elo,
+                    game.user2_elo_after,
+                )
+            } else {
+                (
+                    game.user2_elo,
+                    game.user2_elo_after,
+                    game.user2_elo_delta,
+                    game.user1_elo,
+                    game.user1_elo_after,
+                )
+            };
+        Self {
+            game_id: game.game_id,
+            opponent,
+            result,
+            variant: game.variant,
+            time_control: game.time_control,
+            elo_before,
+            elo_after,
+            elo_change,
+            opp_elo_before,
+            opp_elo_after,
+            rated: game.rated,
+            duration: (game.end_time - game.start_time).num_seconds(),
+            start_time: game.start_time,
+            end_time: game.end_time,
+        }
+    }
+}
+
+impl TttDbConn {
+    pub async fn get_match_history(
+        &self,
+        user_id: i64,
+        filter: GameHistoryFilter,
+    ) -> Result<GameHistoryPage, TttDbErr> {
+        let db = &self.db;
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let mut condition = Condition::all().add(
+            Condition::any()
+                .add(games::Column::User1Id.eq(user_id))
+                .add(games::Column::User2Id.eq(user_id)),
+        );
+        if let Some(cursor) = &filter.cursor {
+            let cursor = Cursor::decode(cursor)?;
+            condition = condition.add(
+                Condition::any()
+                    .add(games::Column::EndTime.lt(cursor.end_time))
+                    .add(
+                        Condition::all()
+                            .add(games::Column::EndTime.eq(cursor.end_time))
+                            .add(games::Column::GameId.lt(cursor.game_id)),
+                    ),
+            );
+        }
+        if let Some(result) = filter.result {
+            condition = condition.add(match result {
+                GameResult::Win => Condition::all().add(games::Column::Winner.eq(user_id)),
+                GameResult::Loss => Condition::all()
+                    .add(games::Column::Winner.is_not_null())
+                    .add(games::Column::Winner.ne(user_id)),
+                GameResult::Draw => Condition::all().add(games::Column::Winner.is_null()).add(
+                    Condition::any()
+                        .add(games::Column::EndReason.is_null())
+                        .add(games::Column::EndReason.ne(EndReason::Aborted.as_str())),
+                ),
+                GameResult::Aborted => {
+                    Condition::all().add(games::Column::EndReason.eq(EndReason::Aborted.as_str()))
+                }
+            });
+        }
+        if let Some(opponent) = &filter.opponent {
+            let opp_id = self.find_user_by_username(opponent).await?.user_id;
+            condition = condition.add(
+                Condition::any()
+                    .add(games::Column::User1Id.eq(opp_id))
+                    .add(games::Column::User2Id.eq(opp_id)),
+            );
+        }
+        if let Some(from) = filter.from {
+            condition = condition.add(games::Column::EndTime.gte(from));
+        }
+        if let Some(to) = filter.to {
+            condition = condition.add(games::Column::EndTime.lte(to));
+        }
+        if let Some(variant) = filter.variant {
+            condition = condition.add(games::Column::Variant.eq(variant.as_str()));
+        }
+        if let Some(time_control) = filter.time_control {
+            condition = condition.add(games::Column::TimeControl.eq(time_control.as_str()));
+        }
+        if let Some(rated) = filter.rated {
+            condition = condition.add(games::Column::Rated.eq(rated));
+        }
+        let mut games = games::Entity::find()
+            .filter(condition)
+            .order_by_desc(games::Column::EndTime)
+            .order_by_desc(games::Column::GameId)
+            .limit(limit + 1)
+            .all(db)
+            .await?;
+        let has_more = games.len() as u64 > limit;
+        games.truncate(limit as usize);
+        let games = self.to_history_entries(user_id, games).await?;
+        let next_cursor = match has_more {
+            true => games.last().map(Cursor::encode),
+            false => None,
+        };
+        Ok(GameHistoryPage { games, next_cursor })
+    }
+    pub(crate) async fn to_history_entries(
+        &self,
+        user_id: i64,
+        games: Vec<games::Model>,
+    ) -> Result<Vec<GameHistoryEntry>, TttDbErr> {
+        let db = &self.db;
+        let opp_ids: Vec<i64> = games
+            .iter()
+            .map(|game| opponent_id(game, user_id))
+            .collect();
+        let usernames: HashMap<i64, String> = users::Entity::find()
+            .filter(users::Column::UserId.is_in(opp_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|user| (user.user_id, user.username))
+            .collect();
+        let entries = games
+            .into_iter()
+            .map(|game| {
+                let opponent = usernames
+                    .get(&opponent_id(&game, user_id))
+                    .cloned()
+                    .unwrap_or_default();
+                GameHistoryEntry::new(game, user_id, opponent)
+            })
+            .collect();
+        Ok(entries)
+    }
+}
+
+pub(crate) fn opponent_id(game: &games::Model, user_id: i64) -> i64 {
+    if game.user1_id == user_id {
+        game.user2_id
+    } else {
+        game.user1_id
+    }
+}
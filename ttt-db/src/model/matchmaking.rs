@@ -1,5 +1,8 @@
+use crate::model::head_to_head::HeadToHeadSummary;
 use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::pool::Pool;
 use crate::util::range::calculate_elo_range;
+use crate::util::rating::is_provisional;
 use crate::util::time::get_time_in_queue;
 use chrono::Utc;
 use redis::AsyncCommands;
@@ -11,99 +14,187 @@ pub struct PlayerData {
     pub user_id: i64,
     pub username: String,
     pub elo: i64,
+    pub provisional: bool,
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct Match {
     pub match_id: Uuid,
+    pub pool: Pool,
+    pub rated: bool,
     pub players: (PlayerData, PlayerData),
+    /// Head-to-head summary seen from the perspective of the first player
+    pub h2h: HeadToHeadSummary,
+}
+
+/// User waiting in the matchmaking queue.
+#[derive(Debug, Serialize, Clone)]
+pub struct QueueEntry {
+    pub user_id: i64,
+    pub username: String,
+    pub pool: Pool,
+    pub elo: i64,
+    /// Seconds since the user entered the queue
+    pub time_waited: i64,
+    /// Matchmaking connection of the user is open
+    pub connected: bool,
 }
 
 impl TttDbConn {
     pub async fn check_if_queued(&self, user_id: i64) -> Result<bool, TttDbErr> {
         let mut rdb = self.rdb.get_async_connection().await?;
-        let res = rdb.zscore::<&str, i64, i64>("mm_pool", user_id).await;
-        match res {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false),
-        }
+        let res: bool = rdb.hexists("mm_queued", user_id).await?;
+        Ok(res)
     }
-    pub async fn insert_user_into_mm_queue(&self, user_id: i64) -> Result<(), TttDbErr> {
+    pub async fn insert_user_into_mm_queue(
+        &self,
+        user_id: i64,
+        pool: Pool,
+    ) -> Result<(), TttDbErr> {
         let mut rdb = self.rdb.get_async_connection().await?;
         if self.check_if_queued(user_id).await? {
             return Err(TttDbErr::UserAlreadyQueued);
         }
-        let elo = self.get_elo(user_id).await?;
-        rdb.zadd("mm_pool", user_id, elo).await?;
+        let rating = self.get_rating(user_id, pool).await?;
+        let _: () = rdb.hset("mm_queued", user_id, pool.to_string()).await?;
+        let _: () = rdb
+            .zadd(format!("mm_pool:{}", pool), user_id, rating.rating as i64)
+            .await?;
+        let _: () = rdb
+            .zadd(
+                format!("mm_deviation:{}", pool),
+                user_id,
+                rating.matchmaking_deviation(),
+            )
+            .await?;
         let time = Utc::now().naive_utc().timestamp();
-        rdb.zadd("mm_time", user_id, time).await?;
+        let _: () = rdb.zadd(format!("mm_time:{}", pool), user_id, time).await?;
         Ok(())
     }
     pub async fn remove_user_from_mm_queue(&self, user_id: i64) -> Result<(), TttDbErr> {
         let mut rdb = self.rdb.get_async_connection().await?;
-        rdb.zrem("mm_pool", user_id).await?;
-        rdb.zrem("mm_time", user_id).await?;
+        let pool: Option<String> = rdb.hget("mm_queued", user_id).await?;
+        let _: () = rdb.hdel("mm_queued", user_id).await?;
+        if let Some(pool) = pool {
+            let _: () = rdb.zrem(format!("mm_pool:{}", pool), user_id).await?;
+            let _: () = rdb.zrem(format!("mm_time:{}", pool), user_id).await?;
+            let _: () = rdb.zrem(format!("mm_deviation:{}", pool), user_id).await?;
+        }
         Ok(())
     }
-    pub async fn create_match(&self, p1_id: i64, p2_id: i64) -> Result<Match, TttDbErr> {
+    /// Returns users waiting in every pool, longest waiting first.
+    /// `connected` is left unset since only the matchmaking worker knows it.
+    pub async fn get_mm_queue(&self) -> Result<Vec<QueueEntry>, TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let mut queue = Vec::<QueueEntry>::new();
+        for pool in Pool::all() {
+            let mm_pool = format!("mm_pool:{}", pool);
+            let users: Vec<(i64, i64)> = rdb
+                .zrange_withscores(format!("mm_time:{}", pool), 0, -1)
+                .await?;
+            for (user_id, time_joined) in users {
+                let elo: Option<i64> = rdb.zscore(&mm_pool, user_id).await?;
+                let user = match self.find_user_by_id(user_id).await {
+                    Ok(user) => user,
+                    Err(TttDbErr::UserNotFound) => continue,
+                    Err(err) => return Err(err),
+                };
+                queue.push(QueueEntry {
+                    user_id,
+                    username: user.username,
+                    pool,
+                    elo: elo.unwrap_or_default(),
+                    time_waited: get_time_in_queue(time_joined),
+                    connected: false,
+                });
+            }
+        }
+        queue.sort_unstable_by_key(|e| std::cmp::Reverse(e.time_waited));
+        Ok(queue)
+    }
+    pub async fn create_match(
+        &self,
+        p1_id: i64,
+        p2_id: i64,
+        pool: Pool,
+    ) -> Result<Match, TttDbErr> {
         let p1 = self.find_user_by_id(p1_id).await?;
         let p2 = self.find_user_by_id(p2_id).await?;
-        let p1_elo = self.get_elo(p1_id).await?;
-        let p2_elo = self.get_elo(p2_id).await?;
+        let p1_rating = self.get_pool_rating(p1_id, pool).await?;
+        let p2_rating = self.get_pool_rating(p2_id, pool).await?;
         let p1 = PlayerData {
             user_id: p1.user_id,
             username: p1.username,
-            elo: p1_elo,
+            elo: p1_rating.elo,
+            provisional: is_provisional(p1_rating.games_played),
         };
         let p2 = PlayerData {
             user_id: p2.user_id,
             username: p2.username,
-            elo: p2_elo,
+            elo: p2_rating.elo,
+            provisional: is_provisional(p2_rating.games_played),
         };
+        let h2h = self.get_head_to_head_summary(p1_id, p2_id).await?;
         let match_id = Uuid::new_v4();
         let new_match = Match {
             match_id,
+            pool,
+            rated: true,
             players: (p1, p2),
+            h2h,
         };
         let mut rdb = self.rdb.get_async_connection().await?;
         let match_message = serde_json::to_string(&new_match).unwrap();
-        rdb.publish("matchmaking", match_message).await?;
+        let _: () = rdb.publish("matchmaking", match_message).await?;
         Ok(new_match)
     }
     pub async fn find_matches(&self) -> Result<Vec<Match>, TttDbErr> {
+        let mut matches = Vec::<Match>::new();
+        for pool in Pool::all() {
+            matches.append(&mut self.find_matches_in_pool(pool).await?);
+        }
+        Ok(matches)
+    }
+    async fn find_matches_in_pool(&self, pool: Pool) -> Result<Vec<Match>, TttDbErr> {
+        let mm_pool = format!("mm_pool:{}", pool);
+        let mm_time = format!("mm_time:{}", pool);
+        let mm_deviation = format!("mm_deviation:{}", pool);
         let mut rdb = self.rdb.get_async_connection().await?;
         let mut rdb2 = self.rdb.get_async_connection().await?;
-        let mut iter = rdb2.zscan::<&str, (i64, i64)>("mm_time").await?;
+        let mut iter = rdb2.zscan::<&str, (i64, i64)>(&mm_time).await?;
         let mut matches = Vec::<Match>::new();
         while let Some((user_id, time_joined)) = iter.next_item().await {
-            let elo = rdb.zscore::<&str, i64, u64>("mm_pool", user_id).await;
+            let elo = rdb.zscore::<&str, i64, u64>(&mm_pool, user_id).await;
             let elo = match elo {
                 Ok(elo) => elo,
                 Err(_) => continue,
             };
+            let deviation: f64 = rdb.zscore(&mm_deviation, user_id).await.unwrap_or(0.0);
             let time = get_time_in_queue(time_joined);
-            let elo_range = calculate_elo_range(time);
+            let elo_range = calculate_elo_range(time, deviation);
+            let blocked = self.get_block_set(user_id).await?;
             let mut possible_opponents = Vec::<(i64, i64)>::new();
             let opponents: Vec<(i64, u64)> = rdb
-                .zrangebyscore_withscores("mm_pool", elo - elo_range, elo + elo_range)
+                .zrangebyscore_withscores(&mm_pool, elo.saturating_sub(elo_range), elo + elo_range)
                 .await?;
             for (opp_id, opp_elo) in opponents {
-                if opp_id == user_id {
+                if opp_id == user_id || blocked.contains(&opp_id) {
                     continue;
                 }
-                let opp_time: i64 = rdb.zscore("mm_time", opp_id).await?;
+                let opp_time: i64 = rdb.zscore(&mm_time, opp_id).await?;
                 let opp_time = get_time_in_queue(opp_time);
-                let opp_range = calculate_elo_range(time);
-                if opp_elo - opp_range <= elo && elo <= opp_elo + opp_range {
+                let opp_deviation: f64 = rdb.zscore(&mm_deviation, opp_id).await.unwrap_or(0.0);
+                let opp_range = calculate_elo_range(opp_time, opp_deviation);
+                if opp_elo.saturating_sub(opp_range) <= elo && elo <= opp_elo + opp_range {
                     possible_opponents.push((opp_id, opp_time));
                 }
             }
             if !possible_opponents.is_empty() {
-                possible_opponents.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+                possible_opponents.sort_unstable_by_key(|o| std::cmp::Reverse(o.1));
                 let opp_id = possible_opponents[0].0;
                 self.remove_user_from_mm_queue(user_id).await?;
                 self.remove_user_from_mm_queue(opp_id).await?;
-                let new_match = self.create_match(user_id, opp_id).await?;
+                let new_match = self.create_match(user_id, opp_id, pool).await?;
                 matches.push(new_match);
             }
         }
@@ -0,0 +1,373 @@
+use std::{collections::HashMap, env};
+
+use chrono::{Duration, Utc};
+use lazy_static::lazy_static;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{
+    entity::*, Condition, DbBackend, FromQueryResult, QueryFilter, QueryOrder, QuerySelect,
+    Statement,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entity::messages::{self, Entity as Messages, Model as MessageModel};
+use crate::entity::user_mutes::{self, Entity as UserMutes};
+use crate::entity::users::{self, Entity as User};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 200;
+
+lazy_static! {
+    /// Messages older than this are deleted unless they were reported.
+    static ref MESSAGE_RETENTION_DAYS: i64 = env::var("MESSAGE_RETENTION_DAYS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessage {
+    pub id: i64,
+    pub from: String,
+    pub to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_id: Option<Uuid>,
+    pub body: String,
+    pub sent_at: DateTimeWithTimeZone,
+}
+
+/// Stored message and whether recipient should receive it.
+/// Messages from muted users are stored but not delivered.
+#[derive(Debug, Clone)]
+pub struct SentMessage {
+    pub message: ChatMessage,
+    pub recipient_id: i64,
+    pub deliver: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MessagePage {
+    /// Only messages with id lower than this are returned
+    pub before: Option<i64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Conversation {
+    pub username: String,
+    pub last_message: ChatMessage,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportedMessage {
+    #[serde(flatten)]
+    pub message: ChatMessage,
+    pub reported_by: String,
+    pub reported_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct LastMessage {
+    id: i64,
+}
+
+impl TttDbConn {
+    /// Filters and stores a message. `game_id` is set for in-game chat.
+    pub async fn send_message(
+        &self,
+        sender_id: i64,
+        recipient_id: i64,
+        game_id: Option<Uuid>,
+        body: &str,
+    ) -> Result<SentMessage, TttDbErr> {
+        let db = &self.db;
+        let body = self
+            .chat_filter
+            .apply(body)
+            .map_err(TttDbErr::InvalidInput)?;
+        let sender = self.find_user_by_id(sender_id).await?;
+        let recipient = self.find_user_by_id(recipient_id).await?;
+        // Guests can only chat with their opponents
+        if game_id.is_none() && (sender.guest || recipient.guest) {
+            return Err(TttDbErr::GuestNotAllowed);
+        }
+        if sender_id == recipient_id {
+            return Err(TttDbErr::InvalidInput(
+                "You can't send messages to yourself.".into(),
+            ));
+        }
+        if self.is_blocked(sender_id, recipient_id).await? {
+            return Err(TttDbErr::UserBlocked);
+        }
+        let deliver = !self.is_muted(recipient_id, sender_id).await?;
+        let message = messages::ActiveModel {
+            sender_id: Set(sender_id),
+            recipient_id: Set(recipient_id),
+            game_id: Set(game_id),
+            body: Set(body),
+            sent_at: Set(Utc::now().into()),
+            reported_by: Set(None),
+            reported_at: Set(None),
+            removed: Set(false),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        let usernames = HashMap::from([
+            (sender.user_id, sender.username),
+            (recipient.user_id, recipient.username),
+        ]);
+        Ok(SentMessage {
+            message: to_chat_message(message, &usernames),
+            recipient_id,
+            deliver,
+        })
+    }
+    pub async fn send_direct_message(
+        &self,
+        sender_id: i64,
+        username: &str,
+        body: &str,
+    ) -> Result<SentMessage, TttDbErr> {
+        let recipient = self.find_user_by_username(username).await?;
+        self.send_message(sender_id, recipient.user_id, None, body)
+            .await
+    }
+    /// Messages between two users, newest first.
+    /// Messages from a muted user are hidden.
+    pub async fn get_conversation(
+        &self,
+        user_id: i64,
+        username: &str,
+        page: MessagePage,
+    ) -> Result<Vec<ChatMessage>, TttDbErr> {
+        let db = &self.db;
+        let other = self.find_user_by_username(username).await?;
+        let limit = page
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let mut sent = Condition::all()
+            .add(messages::Column::SenderId.eq(user_id))
+            .add(messages::Column::RecipientId.eq(other.user_id));
+        let received = Condition::all()
+            .add(messages::Column::SenderId.eq(other.user_id))
+            .add(messages::Column::RecipientId.eq(user_id));
+        if !self.is_muted(user_id, other.user_id).await? {
+            sent = Condition::any().add(sent).add(received);
+        }
+        let mut query = Messages::find()
+            .filter(sent)
+            .filter(messages::Column::GameId.is_null())
+            .filter(messages::Column::Removed.eq(false));
+        if let Some(before) = page.before {
+            query = query.filter(messages::Column::Id.lt(before));
+        }
+        let res = query
+            .order_by_desc(messages::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await?;
+        let me = self.find_user_by_id(user_id).await?;
+        let usernames = HashMap::from([(me.user_id, me.username), (other.user_id, other.username)]);
+        Ok(res
+            .into_iter()
+            .map(|m| to_chat_message(m, &usernames))
+            .collect())
+    }
+    /// Latest direct message of every conversation of the user, newest first.
+    pub async fn get_conversations(&self, user_id: i64) -> Result<Vec<Conversation>, TttDbErr> {
+        let db = &self.db;
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"SELECT DISTINCT ON (LEAST(sender_id, recipient_id), GREATEST(sender_id, recipient_id)) id
+            FROM messages
+            WHERE (sender_id = $1 OR recipient_id = $1) AND game_id IS NULL AND NOT removed
+                AND NOT (recipient_id = $1 AND sender_id IN (SELECT muted_id FROM user_mutes WHERE user_id = $1))
+            ORDER BY LEAST(sender_id, recipient_id), GREATEST(sender_id, recipient_id), id DESC"#,
+            vec![user_id.into()],
+        );
+        let ids: Vec<i64> = LastMessage::find_by_statement(stmt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        let messages = Messages::find()
+            .filter(messages::Column::Id.is_in(ids))
+            .order_by_desc(messages::Column::Id)
+            .all(db)
+            .await?;
+        let usernames = self.get_message_usernames(&messages).await?;
+        Ok(messages
+            .into_iter()
+            .map(|m| {
+                let other = match m.sender_id == user_id {
+                    true => m.recipient_id,
+                    false => m.sender_id,
+                };
+                Conversation {
+                    username: usernames.get(&other).cloned().unwrap_or_default(),
+                    last_message: to_chat_message(m, &usernames),
+                }
+            })
+            .collect())
+    }
+    /// Reports received message to moderators.
+    pub async fn report_message(&self, user_id: i64, message_id: i64) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let message = Messages::find_by_id(message_id)
+            .filter(messages::Column::RecipientId.eq(user_id))
+            .one(db)
+            .await?;
+        let mut message = match message {
+            Some(message) => message.into_active_model(),
+            None => return Err(TttDbErr::MessageNotFound),
+        };
+        message.reported_by = Set(Some(user_id));
+        message.reported_at = Set(Some(Utc::now().into()));
+        message.update(db).await?;
+        Ok(())
+    }
+    pub async fn get_reported_messages(&self) -> Result<Vec<ReportedMessage>, TttDbErr> {
+        let db = &self.db;
+        let messages = Messages::find()
+            .filter(messages::Column::ReportedBy.is_not_null())
+            .filter(messages::Column::Removed.eq(false))
+            .order_by_asc(messages::Column::ReportedAt)
+            .all(db)
+            .await?;
+        let usernames = self.get_message_usernames(&messages).await?;
+        Ok(messages
+            .into_iter()
+            .map(|m| ReportedMessage {
+                reported_by: m
+                    .reported_by
+                    .and_then(|id| usernames.get(&id).cloned())
+                    .unwrap_or_default(),
+                reported_at: m.reported_at,
+                message: to_chat_message(m, &usernames),
+            })
+            .collect())
+    }
+    /// Resolves message report. Removed messages are hidden from both users.
+    /// Returns id of the message sender.
+    pub async fn resolve_message_report(
+        &self,
+        message_id: i64,
+        remove: bool,
+    ) -> Result<i64, TttDbErr> {
+        let db = &self.db;
+        let message = match Messages::find_by_id(message_id).one(db).await? {
+            Some(message) => message,
+            None => return Err(TttDbErr::MessageNotFound),
+        };
+        let sender_id = message.sender_id;
+        let mut message = message.into_active_model();
+        match remove {
+            true => message.removed = Set(true),
+            false => {
+                message.reported_by = Set(None);
+                message.reported_at = Set(None);
+            }
+        }
+        message.update(db).await?;
+        Ok(sender_id)
+    }
+    /// Deletes messages older than the retention period. Reported messages are kept until resolved.
+    pub async fn purge_expired_messages(&self) -> Result<u64, TttDbErr> {
+        let db = &self.db;
+        let cutoff = Utc::now() - Duration::days(*MESSAGE_RETENTION_DAYS);
+        let res = Messages::delete_many()
+            .filter(messages::Column::SentAt.lt(cutoff))
+            .filter(
+                Condition::any()
+                    .add(messages::Column::ReportedBy.is_null())
+                    .add(messages::Column::Removed.eq(true)),
+            )
+            .exec(db)
+            .await?;
+        Ok(res.rows_affected)
+    }
+    async fn get_message_usernames(
+        &self,
+        messages: &[MessageModel],
+    ) -> Result<HashMap<i64, String>, TttDbErr> {
+        let db = &self.db;
+        let mut ids: Vec<i64> = Vec::new();
+        for m in messages {
+            ids.push(m.sender_id);
+            ids.push(m.recipient_id);
+            if let Some(id) = m.reported_by {
+                ids.push(id);
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        let users = User::find()
+            .filter(users::Column::UserId.is_in(ids))
+            .all(db)
+            .await?;
+        Ok(users.into_iter().map(|u| (u.user_id, u.username)).collect())
+    }
+    pub async fn mute_user(&self, user_id: i64, username: &str) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let muted = self.find_user_by_username(username).await?;
+        if self.is_muted(user_id, muted.user_id).await? {
+            return Ok(());
+        }
+        user_mutes::ActiveModel {
+            user_id: Set(user_id),
+            muted_id: Set(muted.user_id),
+            created_on: Set(Utc::now().into()),
+        }
+        .insert(db)
+        .await?;
+        Ok(())
+    }
+    pub async fn unmute_user(&self, user_id: i64, username: &str) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let muted = self.find_user_by_username(username).await?;
+        UserMutes::delete_by_id((user_id, muted.user_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+    /// Usernames of users muted by the user.
+    pub async fn get_muted_users(&self, user_id: i64) -> Result<Vec<String>, TttDbErr> {
+        let db = &self.db;
+        let ids: Vec<i64> = UserMutes::find()
+            .filter(user_mutes::Column::UserId.eq(user_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| m.muted_id)
+            .collect();
+        let users = User::find()
+            .filter(users::Column::UserId.is_in(ids))
+            .order_by_asc(users::Column::Username)
+            .all(db)
+            .await?;
+        Ok(users.into_iter().map(|u| u.username).collect())
+    }
+    /// Returns true if `user_id` muted `other_id`.
+    pub async fn is_muted(&self, user_id: i64, other_id: i64) -> Result<bool, TttDbErr> {
+        let db = &self.db;
+        let res = UserMutes::find_by_id((user_id, other_id)).one(db).await?;
+        Ok(res.is_some())
+    }
+}
+
+fn to_chat_message(m: MessageModel, usernames: &HashMap<i64, String>) -> ChatMessage {
+    ChatMessage {
+        id: m.id,
+        from: usernames.get(&m.sender_id).cloned().unwrap_or_default(),
+        to: usernames.get(&m.recipient_id).cloned().unwrap_or_default(),
+        game_id: m.game_id,
+        body: m.body,
+        sent_at: m.sent_at,
+    }
+}
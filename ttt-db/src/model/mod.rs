@@ -1,5 +1,24 @@
+pub(crate) mod account;
+pub(crate) mod admin;
+pub(crate) mod audit_log;
+pub(crate) mod bans;
+pub(crate) mod blocks;
 mod email_verification;
-mod games;
+pub(crate) mod friends;
+pub(crate) mod gameplay_stats;
+pub(crate) mod games;
+pub(crate) mod head_to_head;
+pub(crate) mod identities;
+pub(crate) mod match_history;
 pub(crate) mod matchmaking;
+pub(crate) mod messages;
+mod password_reset;
+pub(crate) mod presence;
+pub(crate) mod profile;
+pub(crate) mod rating_history;
+pub(crate) mod reports;
+mod sessions;
+pub(crate) mod status;
+pub(crate) mod two_factor;
 mod user;
 mod user_data;
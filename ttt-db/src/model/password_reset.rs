@@ -0,0 +1,78 @@
+use chrono::prelude::*;
+use sea_orm::{entity::*, query::*, ActiveValue::Set};
+use uuid::Uuid;
+
+use crate::entity::{password_reset, users};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::validators::*;
+
+impl TttDbConn {
+    /// Generates a password reset token and passes it to `send_password_reset_email`.
+    /// Does nothing if there is no account with the email, so callers can't tell if it exists.
+    pub async fn request_password_reset<F>(
+        &self,
+        email: &str,
+        send_password_reset_email: F,
+    ) -> Result<(), TttDbErr>
+    where
+        F: FnOnce(String, String, Uuid),
+    {
+        let db = &self.db;
+        let tx = db.begin().await?;
+        let user = users::Entity::find()
+            .filter(users::Column::Email.eq(email.trim()))
+            .one(&tx)
+            .await?;
+        let user = match user {
+            Some(user) if !user.guest => user,
+            _ => return Ok(()),
+        };
+        password_reset::Entity::delete_many()
+            .filter(password_reset::Column::UserId.eq(user.user_id))
+            .exec(&tx)
+            .await?;
+        let uuid = Uuid::new_v4();
+        password_reset::ActiveModel {
+            id: Set(uuid),
+            user_id: Set(user.user_id),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+        tx.commit().await?;
+        send_password_reset_email(user.username, user.email, uuid);
+        Ok(())
+    }
+    /// Consumes a password reset token and sets the new password, returns id of the user.
+    pub async fn reset_password(&self, token: Uuid, password: &str) -> Result<i64, TttDbErr> {
+        let password = password.trim();
+        if !is_valid_password(password) {
+            return Err(TttDbErr::Generic(
+                "Password must be at least 6 characters long.".into(),
+            ));
+        }
+        let db = &self.db;
+        let tx = db.begin().await?;
+        let reset = password_reset::Entity::find_by_id(token).one(&tx).await?;
+        let reset = match reset {
+            Some(reset) => reset,
+            None => return Err(TttDbErr::PasswordResetInvalid),
+        };
+        let user_id = reset.user_id;
+        let expired = Local::now() > reset.expires_at;
+        reset.delete(&tx).await?;
+        if expired {
+            tx.commit().await?;
+            return Err(TttDbErr::PasswordResetInvalid);
+        }
+        let user = users::Entity::find_by_id(user_id).one(&tx).await?;
+        let mut user = match user {
+            Some(user) => user.into_active_model(),
+            None => return Err(TttDbErr::PasswordResetInvalid),
+        };
+        user.password = Set(hash_password(password));
+        user.update(&tx).await?;
+        tx.commit().await?;
+        Ok(user_id)
+    }
+}
@@ -0,0 +1,90 @@
+use redis::AsyncCommands;
+use uuid::Uuid;
+
+use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::pool::Pool;
+
+/// Presence keys expire unless refreshed by a websocket heartbeat.
+/// Must be longer than websocket heartbeat interval.
+const PRESENCE_TTL: usize = 30;
+
+/// Websocket connection user is present with.
+#[derive(Debug, Clone, Copy)]
+pub enum Connection {
+    Lobby,
+    Matchmaking(Pool),
+    Game(Uuid),
+}
+
+impl Connection {
+    fn key(&self, user_id: i64) -> String {
+        let kind = match self {
+            Self::Lobby => "lobby",
+            Self::Matchmaking(_) => "matchmaking",
+            Self::Game(_) => "game",
+        };
+        format!("presence:{}:{}", user_id, kind)
+    }
+    fn value(&self) -> String {
+        match self {
+            Self::Lobby => "1".to_string(),
+            Self::Matchmaking(pool) => pool.to_string(),
+            Self::Game(game_id) => game_id.to_string(),
+        }
+    }
+}
+
+/// Tracks websocket connections of users in Redis.
+/// Every kind of connection is stored under its own key which expires if no connection of that kind
+/// keeps sending heartbeats.
+#[derive(Debug, Clone)]
+pub struct Presence {
+    rdb: redis::Client,
+}
+
+impl Presence {
+    /// Marks user as present. Also used on every heartbeat to refresh the key.
+    pub async fn connect(&self, user_id: i64, conn: Connection) -> Result<(), TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let _: () = rdb
+            .set_ex(conn.key(user_id), conn.value(), PRESENCE_TTL)
+            .await?;
+        Ok(())
+    }
+    /// Lobby key is shared by every lobby connection of a user, so it is left to expire
+    /// instead of being removed while another lobby connection may still be open.
+    pub async fn disconnect(&self, user_id: i64, conn: Connection) -> Result<(), TttDbErr> {
+        if let Connection::Lobby = conn {
+            return Ok(());
+        }
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let _: () = rdb.del(conn.key(user_id)).await?;
+        Ok(())
+    }
+    /// Returns true if user has any open lobby connection.
+    pub async fn is_online(&self, user_id: i64) -> Result<bool, TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let res: bool = rdb.exists(format!("presence:{}:lobby", user_id)).await?;
+        Ok(res)
+    }
+    /// Id of the game user is connected to, if any.
+    pub async fn get_game(&self, user_id: i64) -> Result<Option<Uuid>, TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let game_id: Option<String> = rdb.get(format!("presence:{}:game", user_id)).await?;
+        Ok(game_id.and_then(|id| Uuid::parse_str(&id).ok()))
+    }
+    /// Pool user is searching for a match in, if any.
+    pub async fn get_matchmaking(&self, user_id: i64) -> Result<Option<Pool>, TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let pool: Option<String> = rdb.get(format!("presence:{}:matchmaking", user_id)).await?;
+        Ok(pool.and_then(|pool| pool.parse().ok()))
+    }
+}
+
+impl TttDbConn {
+    pub fn presence(&self) -> Presence {
+        Presence {
+            rdb: self.rdb.clone(),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::Serialize;
+
+use crate::model::match_history::{GameHistoryEntry, GameHistoryFilter};
+use crate::model::status::UserStatus;
+use crate::serializables::PoolRatingMessage;
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+const RECENT_GAMES: u64 = 5;
+
+/// Public view of a user. Must never contain email or moderation data.
+#[derive(Debug, Serialize)]
+pub struct UserProfile {
+    pub username: String,
+    pub created_on: DateTimeWithTimeZone,
+    pub email_verified: bool,
+    pub guest: bool,
+    pub wins: i64,
+    pub losses: i64,
+    pub draws: i64,
+    pub ratings: Vec<ProfileRating>,
+    pub recent_games: Vec<GameHistoryEntry>,
+    pub status: UserStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileRating {
+    #[serde(flatten)]
+    pub rating: PoolRatingMessage,
+    pub peak: i64,
+    pub lowest: i64,
+}
+
+impl TttDbConn {
+    pub async fn get_profile(&self, username: &str) -> Result<UserProfile, TttDbErr> {
+        let user = self.find_user_by_username(username).await?;
+        let stats = self.get_user_stats(user.user_id).await?;
+        let extremes: HashMap<_, _> = self
+            .get_rating_extremes(user.user_id)
+            .await?
+            .into_iter()
+            .map(|r| ((r.variant, r.time_control), (r.peak, r.lowest)))
+            .collect();
+        let ratings = self
+            .get_pool_ratings(user.user_id)
+            .await?
+            .into_iter()
+            .map(|rating| {
+                let (peak, lowest) = extremes
+                    .get(&(rating.variant.clone(), rating.time_control.clone()))
+                    .copied()
+                    .unwrap_or((rating.elo, rating.elo));
+                ProfileRating {
+                    peak: peak.max(rating.elo),
+                    lowest: lowest.min(rating.elo),
+                    rating: rating.into(),
+                }
+            })
+            .collect();
+        let recent_games = self
+            .get_match_history(
+                user.user_id,
+                GameHistoryFilter {
+                    limit: Some(RECENT_GAMES),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .games;
+        let status = self.get_user_status(user.user_id).await?;
+        Ok(UserProfile {
+            username: user.username,
+            created_on: user.created_on,
+            email_verified: user.email_verified,
+            guest: user.guest,
+            wins: stats.wins,
+            losses: stats.losses,
+            draws: stats.draws,
+            ratings,
+            recent_games,
+            status,
+        })
+    }
+}
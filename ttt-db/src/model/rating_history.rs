@@ -0,0 +1,156 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{DbBackend, FromQueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::pool::{Pool, TimeControl, Variant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryBucket {
+    Day,
+    Week,
+}
+
+impl HistoryBucket {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+}
+
+/// Range and grouping of rating history.
+/// Without `bucket` every rating change is returned separately.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RatingHistoryFilter {
+    pub variant: Option<Variant>,
+    pub time_control: Option<TimeControl>,
+    pub from: Option<DateTimeWithTimeZone>,
+    pub to: Option<DateTimeWithTimeZone>,
+    pub bucket: Option<HistoryBucket>,
+}
+
+/// Rating at a point in time. When history is bucketed `time` is the start of the bucket,
+/// `elo` is the rating at the end of it and `game_id` is not set.
+#[derive(Debug, Clone, Serialize, FromQueryResult)]
+pub struct RatingPoint {
+    pub time: DateTimeWithTimeZone,
+    pub elo: i64,
+    pub low: i64,
+    pub high: i64,
+    pub games: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RatingHistory {
+    pub username: String,
+    pub variant: Variant,
+    pub time_control: TimeControl,
+    /// All-time highest rating in the pool
+    pub peak: Option<i64>,
+    /// All-time lowest rating in the pool
+    pub lowest: Option<i64>,
+    pub points: Vec<RatingPoint>,
+}
+
+#[derive(Debug, FromQueryResult)]
+pub(crate) struct RatingExtremes {
+    pub variant: String,
+    pub time_control: String,
+    pub peak: i64,
+    pub lowest: i64,
+}
+
+impl TttDbConn {
+    pub async fn get_rating_history(
+        &self,
+        username: &str,
+        filter: RatingHistoryFilter,
+    ) -> Result<RatingHistory, TttDbErr> {
+        let db = &self.db;
+        let user = self.find_user_by_username(username).await?;
+        let pool = Pool::new(
+            filter.variant.unwrap_or(Pool::default().variant),
+            filter.time_control.unwrap_or(Pool::default().time_control),
+        );
+        let values = vec![
+            user.user_id.into(),
+            pool.variant.as_str().into(),
+            pool.time_control.as_str().into(),
+            filter.from.into(),
+            filter.to.into(),
+        ];
+        let range = r#"user_id = $1 AND variant = $2 AND time_control = $3
+            AND ($4::timestamptz IS NULL OR recorded_at >= $4)
+            AND ($5::timestamptz IS NULL OR recorded_at < $5)"#;
+        let sql = match filter.bucket {
+            Some(bucket) => format!(
+                r#"SELECT date_trunc('{}', recorded_at) AS time,
+                    (array_agg(elo_after ORDER BY recorded_at DESC, id DESC))[1] AS elo,
+                    MIN(elo_after) AS low,
+                    MAX(elo_after) AS high,
+                    COUNT(*) AS games,
+                    NULL::uuid AS game_id
+                FROM rating_history
+                WHERE {}
+                GROUP BY time
+                ORDER BY time"#,
+                bucket.as_str(),
+                range
+            ),
+            None => format!(
+                r#"SELECT recorded_at AS time,
+                    elo_after AS elo,
+                    elo_after AS low,
+                    elo_after AS high,
+                    1::int8 AS games,
+                    game_id
+                FROM rating_history
+                WHERE {}
+                ORDER BY recorded_at, id"#,
+                range
+            ),
+        };
+        let stmt = Statement::from_sql_and_values(DbBackend::Postgres, &sql, values);
+        let points = RatingPoint::find_by_statement(stmt).all(db).await?;
+        let extremes = self
+            .get_rating_extremes(user.user_id)
+            .await?
+            .into_iter()
+            .find(|r| {
+                r.variant == pool.variant.as_str() && r.time_control == pool.time_control.as_str()
+            });
+        Ok(RatingHistory {
+            username: user.username,
+            variant: pool.variant,
+            time_control: pool.time_control,
+            peak: extremes.as_ref().map(|r| r.peak),
+            lowest: extremes.as_ref().map(|r| r.lowest),
+            points,
+        })
+    }
+    /// Highest and lowest rating user had in every pool they played in.
+    pub(crate) async fn get_rating_extremes(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<RatingExtremes>, TttDbErr> {
+        let db = &self.db;
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"SELECT variant, time_control,
+                GREATEST(MAX(elo_before), MAX(elo_after)) AS peak,
+                LEAST(MIN(elo_before), MIN(elo_after)) AS lowest
+            FROM rating_history
+            WHERE user_id = $1
+            GROUP BY variant, time_control"#,
+            vec![user_id.into()],
+        );
+        Ok(RatingExtremes::find_by_statement(stmt).all(db).await?)
+    }
+}
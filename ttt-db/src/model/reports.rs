@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::Utc;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{entity::*, Condition, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entity::games::Entity as Games;
+use crate::entity::reports::{self, Entity as Reports, Model as ReportModel};
+use crate::entity::users::{self, Entity as User};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 200;
+const MAX_DESCRIPTION_LENGTH: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportCategory {
+    Cheating,
+    Abuse,
+    Sandbagging,
+    OffensiveName,
+}
+
+impl ReportCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cheating => "cheating",
+            Self::Abuse => "abuse",
+            Self::Sandbagging => "sandbagging",
+            Self::OffensiveName => "offensive_name",
+        }
+    }
+}
+
+impl FromStr for ReportCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cheating" => Ok(Self::Cheating),
+            "abuse" => Ok(Self::Abuse),
+            "sandbagging" => Ok(Self::Sandbagging),
+            "offensive_name" => Ok(Self::OffensiveName),
+            _ => Err(format!("Unknown report category: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    Open,
+    Assigned,
+    Resolved,
+    Dismissed,
+}
+
+impl ReportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Assigned => "assigned",
+            Self::Resolved => "resolved",
+            Self::Dismissed => "dismissed",
+        }
+    }
+}
+
+impl FromStr for ReportStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Self::Open),
+            "assigned" => Ok(Self::Assigned),
+            "resolved" => Ok(Self::Resolved),
+            "dismissed" => Ok(Self::Dismissed),
+            _ => Err(format!("Unknown report status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewReport {
+    pub username: String,
+    pub game_id: Option<Uuid>,
+    pub category: ReportCategory,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Filters of moderation queue. `before` is the id of the last report of the previous page.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ReportFilter {
+    pub status: Option<ReportStatus>,
+    pub category: Option<ReportCategory>,
+    pub before: Option<i64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportAction {
+    /// Report was not valid
+    Dismiss,
+    /// Report was valid and handled without a ban
+    Resolve,
+    /// Report was valid and reported user is banned
+    Ban,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportResolution {
+    pub action: ReportAction,
+    pub note: Option<String>,
+    /// End of the ban, ban is permanent if not set
+    pub ban_ends: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportEntry {
+    pub id: i64,
+    pub reporter: String,
+    pub reported: String,
+    pub game_id: Option<Uuid>,
+    pub category: ReportCategory,
+    pub description: String,
+    pub status: ReportStatus,
+    pub assignee: Option<String>,
+    pub resolution: Option<String>,
+    pub banned: bool,
+    pub created_on: DateTimeWithTimeZone,
+    pub resolved_on: Option<DateTimeWithTimeZone>,
+}
+
+impl TttDbConn {
+    pub async fn create_report(
+        &self,
+        reporter_id: i64,
+        report: NewReport,
+    ) -> Result<i64, TttDbErr> {
+        let db = &self.db;
+        let reported = self.find_user_by_username(&report.username).await?;
+        if reported.user_id == reporter_id {
+            return Err(TttDbErr::InvalidInput("You can't report yourself.".into()));
+        }
+        if report.description.chars().count() > MAX_DESCRIPTION_LENGTH {
+            return Err(TttDbErr::InvalidInput(format!(
+                "Description can't be longer than {} characters.",
+                MAX_DESCRIPTION_LENGTH
+            )));
+        }
+        // Reported game must have been played between reporter and reported user
+        if let Some(game_id) = report.game_id {
+            let game = Games::find_by_id(game_id).one(db).await?;
+            let played = match game {
+                Some(g) => {
+                    let players = [g.user1_id, g.user2_id];
+                    players.contains(&reporter_id) && players.contains(&reported.user_id)
+                }
+                None => false,
+            };
+            if !played {
+                return Err(TttDbErr::InvalidInput("Game not found.".into()));
+            }
+        }
+        let report = reports::ActiveModel {
+            reporter_id: Set(reporter_id),
+            reported_id: Set(reported.user_id),
+            game_id: Set(report.game_id),
+            category: Set(report.category.as_str().to_string()),
+            description: Set(report.description),
+            status: Set(ReportStatus::Open.as_str().to_string()),
+            assignee_id: Set(None),
+            resolution: Set(None),
+            banned: Set(false),
+            created_on: Set(Utc::now().into()),
+            resolved_on: Set(None),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        Ok(report.id)
+    }
+    /// Moderation queue, oldest reports first.
+    pub async fn get_reports(&self, filter: ReportFilter) -> Result<Vec<ReportEntry>, TttDbErr> {
+        let db = &self.db;
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let mut cond = Condition::all();
+        if let Some(status) = filter.status {
+            cond = cond.add(reports::Column::Status.eq(status.as_str()));
+        }
+        if let Some(category) = filter.category {
+            cond = cond.add(reports::Column::Category.eq(category.as_str()));
+        }
+        if let Some(before) = filter.before {
+            cond = cond.add(reports::Column::Id.lt(before));
+        }
+        let reports = Reports::find()
+            .filter(cond)
+            .order_by_desc(reports::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await?;
+        self.to_report_entries(reports).await
+    }
+    pub async fn get_report(&self, report_id: i64) -> Result<ReportEntry, TttDbErr> {
+        let report = self.find_report(report_id).await?;
+        let mut entries = self.to_report_entries(vec![report]).await?;
+        entries.pop().ok_or(TttDbErr::ReportNotFound)
+    }
+    /// Assigns an open report to a moderator.
+    pub async fn assign_report(&self, report_id: i64, assignee_id: i64) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let report = self.find_report(report_id).await?;
+        if !is_pending(&report) {
+            return Err(TttDbErr::InvalidInput("Report is already closed.".into()));
+        }
+        let mut report = report.into_active_model();
+        report.assignee_id = Set(Some(assignee_id));
+        report.status = Set(ReportStatus::Assigned.as_str().to_string());
+        report.update(db).await?;
+        Ok(())
+    }
+    /// Closes a report, banning the reported user if requested.
+    pub async fn resolve_report(
+        &self,
+        report_id: i64,
+        moderator_id: i64,
+        resolution: ReportResolution,
+    ) -> Result<ReportModel, TttDbErr> {
+        let db = &self.db;
+        let report = self.find_report(report_id).await?;
+        if !is_pending(&report) {
+            return Err(TttDbErr::InvalidInput("Report is already closed.".into()));
+        }
+        if resolution.action == ReportAction::Ban {
+            let reason = resolution
+                .note
+                .clone()
+                .unwrap_or_else(|| format!("Report #{}", report.id));
+            self.ban_user(report.reported_id, resolution.ban_ends, Some(reason))
+                .await?;
+        }
+        let status = match resolution.action {
+            ReportAction::Dismiss => ReportStatus::Dismissed,
+            ReportAction::Resolve | ReportAction::Ban => ReportStatus::Resolved,
+        };
+        let mut report = report.into_active_model();
+        report.status = Set(status.as_str().to_string());
+        report.assignee_id = Set(Some(moderator_id));
+        report.resolution = Set(resolution.note);
+        report.banned = Set(resolution.action == ReportAction::Ban);
+        report.resolved_on = Set(Some(Utc::now().into()));
+        Ok(report.update(db).await?)
+    }
+    async fn find_report(&self, report_id: i64) -> Result<ReportModel, TttDbErr> {
+        let db = &self.db;
+        match Reports::find_by_id(report_id).one(db).await? {
+            Some(report) => Ok(report),
+            None => Err(TttDbErr::ReportNotFound),
+        }
+    }
+    async fn to_report_entries(
+        &self,
+        reports: Vec<ReportModel>,
+    ) -> Result<Vec<ReportEntry>, TttDbErr> {
+        let db = &self.db;
+        let mut ids: Vec<i64> = Vec::new();
+        for r in reports.iter() {
+            ids.push(r.reporter_id);
+            ids.push(r.reported_id);
+            if let Some(id) = r.assignee_id {
+                ids.push(id);
+            }
+        }
+        let usernames: HashMap<i64, String> = User::find()
+            .filter(users::Column::UserId.is_in(ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|u| (u.user_id, u.username))
+            .collect();
+        let username = |id: &i64| usernames.get(id).cloned().unwrap_or_default();
+        Ok(reports
+            .into_iter()
+            .map(|r| ReportEntry {
+                id: r.id,
+                reporter: username(&r.reporter_id),
+                reported: username(&r.reported_id),
+                game_id: r.game_id,
+                category: r.category.parse().unwrap_or(ReportCategory::Abuse),
+                description: r.description,
+                status: r.status.parse().unwrap_or(ReportStatus::Open),
+                assignee: r.assignee_id.as_ref().map(username),
+                resolution: r.resolution,
+                banned: r.banned,
+                created_on: r.created_on,
+                resolved_on: r.resolved_on,
+            })
+            .collect())
+    }
+}
+
+fn is_pending(report: &ReportModel) -> bool {
+    matches!(
+        report.status.parse::<ReportStatus>(),
+        Ok(ReportStatus::Open) | Ok(ReportStatus::Assigned)
+    )
+}
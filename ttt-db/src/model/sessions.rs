@@ -0,0 +1,18 @@
+use redis::AsyncCommands;
+
+use crate::ttt_db::{TttDbConn, TttDbErr};
+
+impl TttDbConn {
+    /// Current session epoch of a user. Sessions created in an older epoch are no longer valid.
+    pub async fn get_session_epoch(&self, user_id: i64) -> Result<i64, TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let epoch: Option<i64> = rdb.get(format!("session_epoch:{}", user_id)).await?;
+        Ok(epoch.unwrap_or_default())
+    }
+    /// Invalidates all existing sessions of a user, returns the new session epoch.
+    pub async fn invalidate_sessions(&self, user_id: i64) -> Result<i64, TttDbErr> {
+        let mut rdb = self.rdb.get_async_connection().await?;
+        let epoch: i64 = rdb.incr(format!("session_epoch:{}", user_id), 1).await?;
+        Ok(epoch)
+    }
+}
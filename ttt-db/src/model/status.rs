@@ -0,0 +1,56 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::pool::Pool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnlineStatus {
+    Offline,
+    Online,
+    InQueue,
+    InGame,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserStatus {
+    pub status: OnlineStatus,
+    pub game_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<Pool>,
+}
+
+impl TttDbConn {
+    pub async fn get_user_status(&self, user_id: i64) -> Result<UserStatus, TttDbErr> {
+        let presence = self.presence();
+        // Player who lost connection mid game is still in game until the game ends
+        let game_id = match presence.get_game(user_id).await? {
+            Some(game_id) => Some(game_id),
+            None => self.get_user_active_game(user_id).await?,
+        };
+        if let Some(game_id) = game_id {
+            return Ok(UserStatus {
+                status: OnlineStatus::InGame,
+                game_id: Some(game_id),
+                pool: None,
+            });
+        }
+        if let Some(pool) = presence.get_matchmaking(user_id).await? {
+            return Ok(UserStatus {
+                status: OnlineStatus::InQueue,
+                game_id: None,
+                pool: Some(pool),
+            });
+        }
+        let status = match presence.is_online(user_id).await? {
+            true => OnlineStatus::Online,
+            false => OnlineStatus::Offline,
+        };
+        Ok(UserStatus {
+            status,
+            game_id: None,
+            pool: None,
+        })
+    }
+}
@@ -0,0 +1,233 @@
+use chrono::Utc;
+use redis::AsyncCommands;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{
+    entity::*, Condition, ConnectionTrait, PaginatorTrait, QueryFilter, TransactionTrait,
+};
+use serde::Serialize;
+
+use crate::entity::recovery_codes::{self, Entity as RecoveryCodes};
+use crate::entity::user_totp::{self, Entity as UserTotp, Model as UserTotpModel};
+use crate::ttt_db::{TttDbConn, TttDbErr};
+use crate::util::totp::*;
+
+const RECOVERY_CODE_COUNT: usize = 10;
+/// Wrong codes a user can enter before further attempts are refused.
+const MAX_TWO_FACTOR_ATTEMPTS: i64 = 5;
+/// How long failed attempts are remembered, in seconds. Every failed attempt extends it.
+const TWO_FACTOR_ATTEMPTS_TTL: usize = 900;
+
+/// Secret of a new authenticator, shown to the user once.
+#[derive(Debug, Serialize)]
+pub struct TotpEnrollment {
+    /// Base32 encoded secret, for authenticators which can't scan the provisioning uri
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    pub recovery_codes_left: u64,
+}
+
+async fn replace_recovery_codes<C: ConnectionTrait>(
+    db: &C,
+    user_id: i64,
+) -> Result<Vec<String>, TttDbErr> {
+    RecoveryCodes::delete_many()
+        .filter(recovery_codes::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+    let models = codes.iter().map(|code| recovery_codes::ActiveModel {
+        user_id: Set(user_id),
+        code_hash: Set(hash_recovery_code(code)),
+        ..Default::default()
+    });
+    RecoveryCodes::insert_many(models).exec(db).await?;
+    Ok(codes)
+}
+
+impl TttDbConn {
+    async fn find_confirmed_totp(&self, user_id: i64) -> Result<Option<UserTotpModel>, TttDbErr> {
+        let db = &self.db;
+        let totp = UserTotp::find_by_id(user_id)
+            .filter(user_totp::Column::ConfirmedOn.is_not_null())
+            .one(db)
+            .await?;
+        Ok(totp)
+    }
+    pub async fn has_two_factor(&self, user_id: i64) -> Result<bool, TttDbErr> {
+        Ok(self.find_confirmed_totp(user_id).await?.is_some())
+    }
+    pub async fn get_two_factor_status(&self, user_id: i64) -> Result<TwoFactorStatus, TttDbErr> {
+        let db = &self.db;
+        let enabled = self.has_two_factor(user_id).await?;
+        let recovery_codes_left = RecoveryCodes::find()
+            .filter(recovery_codes::Column::UserId.eq(user_id))
+            .filter(recovery_codes::Column::UsedOn.is_null())
+            .count(db)
+            .await?;
+        Ok(TwoFactorStatus {
+            enabled,
+            recovery_codes_left,
+        })
+    }
+    /// Generates a new TOTP secret. It is not used until confirmed with a first code,
+    /// starting over replaces an unconfirmed secret.
+    pub async fn enroll_totp(&self, user_id: i64) -> Result<TotpEnrollment, TttDbErr> {
+        let db = &self.db;
+        let user = self.find_user_by_id(user_id).await?;
+        if user.guest {
+            return Err(TttDbErr::GuestNotAllowed);
+        }
+        if self.has_two_factor(user_id).await? {
+            return Err(TttDbErr::InvalidInput(
+                "Two-factor authentication is already enabled.".into(),
+            ));
+        }
+        let secret = generate_secret();
+        let tx = db.begin().await?;
+        UserTotp::delete_by_id(user_id).exec(&tx).await?;
+        user_totp::ActiveModel {
+            user_id: Set(user_id),
+            secret: Set(secret.clone()),
+            last_step: Set(None),
+            confirmed_on: Set(None),
+            created_on: Set(Utc::now().into()),
+        }
+        .insert(&tx)
+        .await?;
+        tx.commit().await?;
+        Ok(TotpEnrollment {
+            secret: base32_encode(&secret),
+            provisioning_uri: provisioning_uri(&secret, &user.username),
+        })
+    }
+    /// Enables two-factor authentication after checking the first code, returns recovery codes.
+    pub async fn confirm_totp(&self, user_id: i64, code: &str) -> Result<Vec<String>, TttDbErr> {
+        let db = &self.db;
+        let totp = match UserTotp::find_by_id(user_id).one(db).await? {
+            Some(totp) if totp.confirmed_on.is_none() => totp,
+            Some(_) => {
+                return Err(TttDbErr::InvalidInput(
+                    "Two-factor authentication is already enabled.".into(),
+                ))
+            }
+            None => {
+                return Err(TttDbErr::InvalidInput(
+                    "Start two-factor authentication setup first.".into(),
+                ))
+            }
+        };
+        let step = verify_code(&totp.secret, code, None).ok_or(TttDbErr::TwoFactorInvalid)?;
+        let tx = db.begin().await?;
+        let mut totp = totp.into_active_model();
+        totp.last_step = Set(Some(step));
+        totp.confirmed_on = Set(Some(Utc::now().into()));
+        totp.update(&tx).await?;
+        let codes = replace_recovery_codes(&tx, user_id).await?;
+        tx.commit().await?;
+        Ok(codes)
+    }
+    /// Checks a TOTP code or an unused recovery code. Every code can be used only once.
+    /// Users who entered too many wrong codes are locked out for a while.
+    pub async fn verify_two_factor(&self, user_id: i64, code: &str) -> Result<(), TttDbErr> {
+        let key = format!("two_factor_attempts:{}", user_id);
+        let mut rdb = self.rdb.get_async_connection().await?;
+        // Attempt is counted before checking, so concurrent requests can't exceed the limit
+        let (attempts,): (i64,) = redis::pipe()
+            .atomic()
+            .incr(&key, 1)
+            .expire(&key, TWO_FACTOR_ATTEMPTS_TTL)
+            .ignore()
+            .query_async(&mut rdb)
+            .await?;
+        if attempts > MAX_TWO_FACTOR_ATTEMPTS {
+            return Err(TttDbErr::TwoFactorLocked);
+        }
+        self.check_two_factor_code(user_id, code).await?;
+        let _: () = rdb.del(&key).await?;
+        Ok(())
+    }
+    async fn check_two_factor_code(&self, user_id: i64, code: &str) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let totp = match self.find_confirmed_totp(user_id).await? {
+            Some(totp) => totp,
+            None => {
+                return Err(TttDbErr::InvalidInput(
+                    "Two-factor authentication is not enabled.".into(),
+                ))
+            }
+        };
+        if let Some(step) = verify_code(&totp.secret, code, totp.last_step) {
+            // Conditional update so a code can't be used twice by concurrent requests
+            let res = UserTotp::update_many()
+                .set(user_totp::ActiveModel {
+                    last_step: Set(Some(step)),
+                    ..Default::default()
+                })
+                .filter(user_totp::Column::UserId.eq(user_id))
+                .filter(
+                    Condition::any()
+                        .add(user_totp::Column::LastStep.is_null())
+                        .add(user_totp::Column::LastStep.lt(step)),
+                )
+                .exec(db)
+                .await?;
+            if res.rows_affected == 1 {
+                return Ok(());
+            }
+            return Err(TttDbErr::TwoFactorInvalid);
+        }
+        let res = RecoveryCodes::update_many()
+            .set(recovery_codes::ActiveModel {
+                used_on: Set(Some(Utc::now().into())),
+                ..Default::default()
+            })
+            .filter(recovery_codes::Column::UserId.eq(user_id))
+            .filter(recovery_codes::Column::CodeHash.eq(hash_recovery_code(code)))
+            .filter(recovery_codes::Column::UsedOn.is_null())
+            .exec(db)
+            .await?;
+        match res.rows_affected {
+            0 => Err(TttDbErr::TwoFactorInvalid),
+            _ => Ok(()),
+        }
+    }
+    /// Replaces all recovery codes after checking a code, returns the new ones.
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: i64,
+        code: &str,
+    ) -> Result<Vec<String>, TttDbErr> {
+        self.verify_two_factor(user_id, code).await?;
+        let db = &self.db;
+        let tx = db.begin().await?;
+        let codes = replace_recovery_codes(&tx, user_id).await?;
+        tx.commit().await?;
+        Ok(codes)
+    }
+    /// Disables two-factor authentication after checking a code. Admins can't disable it.
+    pub async fn disable_two_factor(&self, user_id: i64, code: &str) -> Result<(), TttDbErr> {
+        let user = self.find_user_by_id(user_id).await?;
+        if user.is_admin {
+            return Err(TttDbErr::InvalidInput(
+                "Admin accounts must use two-factor authentication.".into(),
+            ));
+        }
+        self.verify_two_factor(user_id, code).await?;
+        let db = &self.db;
+        let tx = db.begin().await?;
+        UserTotp::delete_by_id(user_id).exec(&tx).await?;
+        RecoveryCodes::delete_many()
+            .filter(recovery_codes::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -11,7 +11,7 @@ use uuid::Uuid;
 use crate::entity::email_verification::ActiveModel as EmailVerificationActiveModel;
 use crate::entity::users::Entity as User;
 use crate::entity::users::Model as UserModel;
-use crate::entity::{user_stats, users};
+use crate::entity::{email_verification, user_stats, users};
 use crate::serializables::UserMessage;
 
 impl TttDbConn {
@@ -23,6 +23,18 @@ impl TttDbConn {
             None => Err(TttDbErr::UserNotFound),
         }
     }
+    pub async fn find_user_by_username(&self, username: &str) -> Result<UserModel, TttDbErr> {
+        let db = &self.db;
+        let user = User::find()
+            .filter(users::Column::Username.eq(username))
+            .filter(users::Column::DeletedOn.is_null())
+            .one(db)
+            .await?;
+        match user {
+            Some(user) => Ok(user),
+            None => Err(TttDbErr::UserNotFound),
+        }
+    }
     pub async fn sign_up<F>(
         &self,
         mut user: UserMessage,
@@ -248,12 +260,147 @@ impl TttDbConn {
             .one(db)
             .await?;
         if let Some(db_user) = db_user {
-            match verify_password(&password, &db_user.password) {
-                true => Ok(db_user),
-                false => Err(TttDbErr::InvalidPassword),
+            if !verify_password(&password, &db_user.password) {
+                return Err(TttDbErr::InvalidPassword);
+            }
+            // Upgrade hashes with the legacy salt or outdated parameters
+            if needs_rehash(&db_user.password) {
+                let mut user = db_user.into_active_model();
+                user.password = Set(hash_password(&password));
+                return Ok(user.update(db).await?);
             }
+            Ok(db_user)
         } else {
             Err(TttDbErr::UserNotFound)
         }
     }
+    /// Changes password of a user after checking their current password.
+    pub async fn change_password(
+        &self,
+        user_id: i64,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<(), TttDbErr> {
+        let db = &self.db;
+        let user = self.find_user_by_id(user_id).await?;
+        if user.guest {
+            return Err(TttDbErr::GuestNotAllowed);
+        }
+        if !verify_password(current_password, &user.password) {
+            return Err(TttDbErr::InvalidPassword);
+        }
+        let new_password = new_password.trim();
+        if !is_valid_password(new_password) {
+            return Err(TttDbErr::Generic(
+                "Password must be at least 6 characters long.".into(),
+            ));
+        }
+        let mut user = user.into_active_model();
+        user.password = Set(hash_password(new_password));
+        user.update(db).await?;
+        Ok(())
+    }
+    /// Changes username of a user, returns the updated user.
+    pub async fn change_username(
+        &self,
+        user_id: i64,
+        username: &str,
+    ) -> Result<UserModel, TttDbErr> {
+        let username = username.trim();
+        if username.is_empty() {
+            return Err(TttDbErr::Generic("Username field cannot be empty.".into()));
+        }
+        if !is_valid_username(username) {
+            return Err(TttDbErr::Generic("Username must consist of alphanumeric characters and cannot contain special characters.".into()));
+        }
+        let db = &self.db;
+        let user = self.find_user_by_id(user_id).await?;
+        if user.guest {
+            return Err(TttDbErr::GuestNotAllowed);
+        }
+        let mut user = user.into_active_model();
+        user.username = Set(username.to_string());
+        match user.update(db).await {
+            Ok(user) => Ok(user),
+            Err(sea_orm::DbErr::Query(RuntimeErr::SqlxError(s))) => {
+                if let Some(err) = s.as_database_error() {
+                    if let Some("users_unique_username") = err.constraint() {
+                        return Err(TttDbErr::UsernameConfilct);
+                    }
+                    return Err(TttDbErr::Unhandled);
+                }
+                warn!("{}", s);
+                Err(TttDbErr::Unhandled)
+            }
+            Err(err) => Err(TttDbErr::DbErr(err)),
+        }
+    }
+    /// Changes email of a user after checking their password.
+    /// New email has to be verified again, returns the old email.
+    pub async fn change_email<F>(
+        &self,
+        user_id: i64,
+        password: &str,
+        email: &str,
+        send_verification_email: F,
+    ) -> Result<String, TttDbErr>
+    where
+        F: FnOnce(String, String, Uuid),
+    {
+        let email = email.trim().to_string();
+        if !is_valid_email(&email) {
+            return Err(TttDbErr::Generic("Invalid email format.".into()));
+        }
+        let db = &self.db;
+        let tx = db.begin().await?;
+        let user = users::Entity::find_by_id(user_id).one(&tx).await?;
+        let user = match user {
+            Some(user) => user,
+            None => return Err(TttDbErr::UserNotFound),
+        };
+        if user.guest {
+            return Err(TttDbErr::GuestNotAllowed);
+        }
+        if !verify_password(password, &user.password) {
+            return Err(TttDbErr::InvalidPassword);
+        }
+        if user.email == email {
+            return Err(TttDbErr::InvalidInput("This is already your email.".into()));
+        }
+        let old_email = user.email.clone();
+        let username = user.username.clone();
+        email_verification::Entity::delete_many()
+            .filter(email_verification::Column::Email.eq(old_email.clone()))
+            .exec(&tx)
+            .await?;
+        let mut user = user.into_active_model();
+        user.email = Set(email.clone());
+        user.email_verified = Set(false);
+        if let Err(err) = user.update(&tx).await {
+            match err {
+                sea_orm::DbErr::Query(RuntimeErr::SqlxError(s)) => {
+                    if let Some(err) = s.as_database_error() {
+                        if let Some("users_unique_email") = err.constraint() {
+                            return Err(TttDbErr::EmailConflict);
+                        }
+                        return Err(TttDbErr::Unhandled);
+                    }
+                    warn!("{}", s);
+                    return Err(TttDbErr::Unhandled);
+                }
+                _ => return Err(TttDbErr::DbErr(err)),
+            }
+        }
+        let uuid = Uuid::new_v4();
+        EmailVerificationActiveModel {
+            email: Set(email.clone()),
+            id: Set(uuid),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await?;
+        tx.commit().await?;
+        send_verification_email(username, email, uuid);
+        Ok(old_email)
+    }
 }
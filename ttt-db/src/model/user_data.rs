@@ -1,12 +1,34 @@
-use sea_orm::EntityTrait;
+use sea_orm::{ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, QueryFilter, Statement};
 
 use crate::ttt_db::{TttDbConn, TttDbErr};
 
-use crate::entity::prelude::UserStats;
-use crate::entity::user_stats;
+use crate::entity::prelude::{UserRating, UserStats};
+use crate::entity::{user_ratings, user_stats};
+use crate::serializables::{PoolRatingMessage, UserDataMessage};
+use crate::util::pool::Pool;
+use crate::util::rating::Rating;
 
 impl TttDbConn {
-    pub async fn get_user_data(&self, user_id: i64) -> Result<UserStats, TttDbErr> {
+    pub async fn get_user_data(&self, user_id: i64) -> Result<UserDataMessage, TttDbErr> {
+        let user = self.find_user_by_id(user_id).await?;
+        let stats = self.get_user_stats(user_id).await?;
+        let elo = self.get_elo(user_id, Pool::default()).await?;
+        let ratings = self
+            .get_pool_ratings(user_id)
+            .await?
+            .into_iter()
+            .map(PoolRatingMessage::from)
+            .collect();
+        Ok(UserDataMessage {
+            username: user.username,
+            elo,
+            wins: stats.wins,
+            losses: stats.losses,
+            draws: stats.draws,
+            ratings,
+        })
+    }
+    pub(crate) async fn get_user_stats(&self, user_id: i64) -> Result<UserStats, TttDbErr> {
         let db = &self.db;
         let res = user_stats::Entity::find_by_id(user_id).one(db).await?;
         match res {
@@ -14,12 +36,58 @@ impl TttDbConn {
             Some(res) => Ok(res),
         }
     }
-    pub async fn get_elo(&self, user_id: i64) -> Result<i64, TttDbErr> {
+    pub async fn get_pool_ratings(&self, user_id: i64) -> Result<Vec<UserRating>, TttDbErr> {
         let db = &self.db;
-        let res = user_stats::Entity::find_by_id(user_id).one(db).await?;
-        match res {
-            None => Err(TttDbErr::UserNotFound),
-            Some(res) => Ok(res.elo),
+        let res = user_ratings::Entity::find()
+            .filter(user_ratings::Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+        Ok(res)
+    }
+    /// Returns user's rating in the given pool.
+    /// Pool ratings are created lazily, the first time they are needed.
+    pub async fn get_pool_rating(&self, user_id: i64, pool: Pool) -> Result<UserRating, TttDbErr> {
+        let db = &self.db;
+        let variant = pool.variant.as_str().to_string();
+        let time_control = pool.time_control.as_str().to_string();
+        let find = || {
+            user_ratings::Entity::find_by_id((user_id, variant.clone(), time_control.clone()))
+                .one(db)
+        };
+        if let Some(res) = find().await? {
+            return Ok(res);
+        }
+        self.find_user_by_id(user_id).await?;
+        // Concurrent first reads may both try to create the rating, the loser keeps the winner's row
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"INSERT INTO user_ratings (user_id, variant, time_control)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING"#,
+            vec![
+                user_id.into(),
+                variant.clone().into(),
+                time_control.clone().into(),
+            ],
+        );
+        db.execute(stmt).await?;
+        match find().await? {
+            Some(res) => Ok(res),
+            None => Err(TttDbErr::Unhandled),
         }
     }
+    pub async fn get_elo(&self, user_id: i64, pool: Pool) -> Result<i64, TttDbErr> {
+        let res = self.get_pool_rating(user_id, pool).await?;
+        Ok(res.elo)
+    }
+    pub(crate) async fn get_rating(&self, user_id: i64, pool: Pool) -> Result<Rating, TttDbErr> {
+        let res = self.get_pool_rating(user_id, pool).await?;
+        let rating = Rating {
+            rating: res.elo as f64,
+            deviation: res.deviation,
+            volatility: res.volatility,
+            games_played: res.games_played,
+        };
+        Ok(rating.decay(res.last_game))
+    }
 }
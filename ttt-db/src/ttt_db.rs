@@ -1,10 +1,14 @@
 use redis::RedisError;
 use sea_orm::{DatabaseConnection, DbErr};
+use std::sync::Arc;
+
+use crate::util::chat_filter::ChatFilter;
 
 #[derive(Debug, Clone)]
 pub struct TttDbConn {
     pub(crate) db: DatabaseConnection,
     pub(crate) rdb: redis::Client,
+    pub(crate) chat_filter: Arc<ChatFilter>,
 }
 
 impl TttDbConn {
@@ -14,8 +18,14 @@ impl TttDbConn {
                 .await
                 .expect("Cannot connect to database"),
             rdb: redis::Client::open(redis_conn_str).expect("Error connecting to redis backend"),
+            chat_filter: Arc::new(ChatFilter::from_env()),
         }
     }
+    /// Replaces filter chat messages pass through.
+    pub fn with_chat_filter(mut self, chat_filter: ChatFilter) -> Self {
+        self.chat_filter = Arc::new(chat_filter);
+        self
+    }
     pub fn get_rdb(&self) -> redis::Client {
         self.rdb.clone()
     }
@@ -31,6 +41,18 @@ pub enum TttDbErr {
     Unhandled,
     UserAlreadyQueued,
     EmailVerifyExpired,
+    GuestNotAllowed,
+    FriendRequestNotFound,
+    FriendshipExists,
+    MessageNotFound,
+    UserBlocked,
+    ReportNotFound,
+    GameNotFound,
+    PasswordResetInvalid,
+    IdentityLinked,
+    TwoFactorInvalid,
+    TwoFactorLocked,
+    InvalidInput(String),
     Generic(String),
     DbErr(sea_orm::DbErr),
 }
@@ -46,6 +68,20 @@ impl ToString for TttDbErr {
             Self::Unhandled => "Unhandled error occured.".into(),
             Self::UserAlreadyQueued => "User is already in the matchmaking queue.".into(),
             Self::EmailVerifyExpired => "Email verification link expired.".into(),
+            Self::GuestNotAllowed => "Guest accounts must be claimed first.".into(),
+            Self::FriendRequestNotFound => "Friend request not found.".into(),
+            Self::FriendshipExists => "Friend request already sent or accepted.".into(),
+            Self::MessageNotFound => "Message not found.".into(),
+            Self::UserBlocked => "You can't interact with this user.".into(),
+            Self::ReportNotFound => "Report not found.".into(),
+            Self::GameNotFound => "Game not found.".into(),
+            Self::PasswordResetInvalid => "Password reset code is invalid or expired.".into(),
+            Self::IdentityLinked => "This account is already linked to another user.".into(),
+            Self::TwoFactorInvalid => "Invalid two-factor authentication code.".into(),
+            Self::TwoFactorLocked => {
+                "Too many invalid two-factor authentication codes, try again later.".into()
+            }
+            Self::InvalidInput(s) => s.to_string(),
             Self::Generic(s) => s.to_string(),
             Self::DbErr(err) => err.to_string(),
         }
@@ -0,0 +1,165 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{env, fmt::Debug};
+
+/// Default maximum length of a chat message, in characters.
+const MAX_MESSAGE_LENGTH: usize = 500;
+
+/// Single step of chat message filtering.
+/// Returns filtered message or the reason message was rejected.
+pub trait MessageFilter: Send + Sync {
+    fn filter(&self, msg: String) -> Result<String, String>;
+}
+
+/// Rejects messages longer than the limit.
+pub struct LengthFilter(pub usize);
+
+impl MessageFilter for LengthFilter {
+    fn filter(&self, msg: String) -> Result<String, String> {
+        match msg.chars().count() > self.0 {
+            true => Err(format!(
+                "Message can't be longer than {} characters.",
+                self.0
+            )),
+            false => Ok(msg),
+        }
+    }
+}
+
+/// Rejects messages containing links.
+pub struct UrlFilter;
+
+impl MessageFilter for UrlFilter {
+    fn filter(&self, msg: String) -> Result<String, String> {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"(?i)(https?://|www\.|\b[a-z0-9-]+\.(com|net|org|io|gg|ly|me)\b)")
+                    .unwrap();
+        }
+        match RE.is_match(&msg) {
+            true => Err("Links are not allowed in chat.".into()),
+            false => Ok(msg),
+        }
+    }
+}
+
+/// Masks listed words with asterisks.
+pub struct ProfanityFilter {
+    words: Vec<Regex>,
+}
+
+impl ProfanityFilter {
+    pub fn new<S: AsRef<str>>(words: &[S]) -> Self {
+        let words = words
+            .iter()
+            .map(|w| w.as_ref().trim())
+            .filter(|w| !w.is_empty())
+            .filter_map(|w| Regex::new(&format!(r"(?i)\b{}\b", regex::escape(w))).ok())
+            .collect();
+        Self { words }
+    }
+}
+
+impl MessageFilter for ProfanityFilter {
+    fn filter(&self, mut msg: String) -> Result<String, String> {
+        for re in self.words.iter() {
+            msg = re
+                .replace_all(&msg, |caps: &regex::Captures| {
+                    "*".repeat(caps[0].chars().count())
+                })
+                .into_owned();
+        }
+        Ok(msg)
+    }
+}
+
+/// Chain of filters every chat message passes through before it is stored.
+#[derive(Default)]
+pub struct ChatFilter {
+    filters: Vec<Box<dyn MessageFilter>>,
+}
+
+impl Debug for ChatFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ChatFilter({} filters)", self.filters.len())
+    }
+}
+
+impl ChatFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with<F: MessageFilter + 'static>(mut self, filter: F) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+    /// Filters configured with `CHAT_MAX_LENGTH`, `CHAT_BLOCK_URLS` and `CHAT_PROFANITY_LIST` env vars.
+    pub fn from_env() -> Self {
+        let max_length = env::var("CHAT_MAX_LENGTH")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(MAX_MESSAGE_LENGTH);
+        let mut filter = Self::new().with(LengthFilter(max_length));
+        if env::var("CHAT_BLOCK_URLS").map_or(true, |s| s != "false") {
+            filter = filter.with(UrlFilter);
+        }
+        if let Ok(words) = env::var("CHAT_PROFANITY_LIST") {
+            let words: Vec<&str> = words.split(',').collect();
+            filter = filter.with(ProfanityFilter::new(&words));
+        }
+        filter
+    }
+    pub fn apply(&self, msg: &str) -> Result<String, String> {
+        let mut msg = msg.trim().to_string();
+        if msg.is_empty() {
+            return Err("Message is empty.".into());
+        }
+        for filter in self.filters.iter() {
+            msg = filter.filter(msg)?;
+        }
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_limit() {
+        let filter = ChatFilter::new().with(LengthFilter(5));
+        assert_eq!(filter.apply("hello"), Ok("hello".to_string()));
+        assert_eq!(filter.apply("  hello  "), Ok("hello".to_string()));
+        assert_eq!(
+            filter.apply("źdźbło"),
+            Err("Message can't be longer than 5 characters.".to_string())
+        );
+        assert!(filter.apply("hello!").is_err());
+        assert!(filter.apply("   ").is_err());
+    }
+
+    #[test]
+    fn url_blocking() {
+        let filter = ChatFilter::new().with(UrlFilter);
+        assert!(filter.apply("see https://example.org").is_err());
+        assert!(filter.apply("HTTP://EXAMPLE.ORG").is_err());
+        assert!(filter.apply("go to www.example").is_err());
+        assert!(filter.apply("join tictactoe.gg now").is_err());
+        assert!(filter.apply("good game. well played").is_ok());
+    }
+
+    #[test]
+    fn profanity_masking() {
+        let filter = ChatFilter::new().with(ProfanityFilter::new(&["darn", " heck ", ""]));
+        assert_eq!(filter.apply("darn it"), Ok("**** it".to_string()));
+        assert_eq!(
+            filter.apply("DaRn, what the Heck"),
+            Ok("****, what the ****".to_string())
+        );
+        // Only whole words are masked
+        assert_eq!(
+            filter.apply("darnation checked"),
+            Ok("darnation checked".to_string())
+        );
+    }
+}
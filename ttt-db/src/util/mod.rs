@@ -1,4 +1,8 @@
+pub mod chat_filter;
+pub mod pool;
 pub(crate) mod range;
+pub mod rating;
 pub mod serializables;
 pub(crate) mod time;
+pub(crate) mod totp;
 pub(crate) mod validators;
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    Classic,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 1] = [Variant::Classic];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Classic => "classic",
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Self::Classic),
+            _ => Err(format!("Unknown variant: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeControl {
+    Bullet,
+    Blitz,
+    Rapid,
+}
+
+impl TimeControl {
+    pub const ALL: [TimeControl; 3] = [TimeControl::Bullet, TimeControl::Blitz, TimeControl::Rapid];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bullet => "bullet",
+            Self::Blitz => "blitz",
+            Self::Rapid => "rapid",
+        }
+    }
+    /// Time each player has for the whole game, in seconds.
+    pub fn seconds(&self) -> f32 {
+        match self {
+            Self::Bullet => 15.0,
+            Self::Blitz => 60.0,
+            Self::Rapid => 180.0,
+        }
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bullet" => Ok(Self::Bullet),
+            "blitz" => Ok(Self::Blitz),
+            "rapid" => Ok(Self::Rapid),
+            _ => Err(format!("Unknown time control: {}", s)),
+        }
+    }
+}
+
+/// Rating pool. Every combination of variant and time control is rated separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pool {
+    pub variant: Variant,
+    pub time_control: TimeControl,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Self {
+            variant: Variant::Classic,
+            time_control: TimeControl::Blitz,
+        }
+    }
+}
+
+impl Pool {
+    pub fn new(variant: Variant, time_control: TimeControl) -> Self {
+        Self {
+            variant,
+            time_control,
+        }
+    }
+    pub fn all() -> Vec<Pool> {
+        let mut pools = Vec::new();
+        for variant in Variant::ALL {
+            for time_control in TimeControl::ALL {
+                pools.push(Pool::new(variant, time_control));
+            }
+        }
+        pools
+    }
+}
+
+impl Display for Pool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}",
+            self.variant.as_str(),
+            self.time_control.as_str()
+        )
+    }
+}
+
+impl FromStr for Pool {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((variant, time_control)) => Ok(Self {
+                variant: variant.parse()?,
+                time_control: time_control.parse()?,
+            }),
+            None => Err(format!("Invalid rating pool: {}", s)),
+        }
+    }
+}
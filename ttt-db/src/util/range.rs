@@ -1,11 +1,45 @@
 use std::cmp::min;
 
-pub(crate) fn calculate_elo_range(time: i64) -> u64 {
+const MAX_ELO_RANGE: u64 = 500;
+
+/// Calculates the range of ratings a player can be matched against.
+/// Range widens with time spent in queue and with rating deviation,
+/// so players with uncertain ratings find opponents faster.
+pub(crate) fn calculate_elo_range(time: i64, deviation: f64) -> u64 {
     let elo_range: u64 = 50;
-    let time: u32 = time.try_into().unwrap();
+    let time: u32 = time.try_into().unwrap_or(0);
     let elo_range: (u64, bool) = elo_range.overflowing_pow(time / 10);
-    match elo_range.1 {
-        true => 500,
-        false => min(elo_range.0, 500),
+    let elo_range = match elo_range.1 {
+        true => MAX_ELO_RANGE,
+        false => min(elo_range.0, MAX_ELO_RANGE),
+    };
+    let uncertainty = (deviation.max(0.0) / 2.0) as u64;
+    min(elo_range + uncertainty, MAX_ELO_RANGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deviation_widens_range() {
+        assert_eq!(calculate_elo_range(0, 0.0), 1);
+        assert_eq!(calculate_elo_range(0, 100.0), 51);
+        assert_eq!(calculate_elo_range(10, 350.0), 225);
+    }
+
+    #[test]
+    fn negative_deviation_is_ignored() {
+        assert_eq!(
+            calculate_elo_range(10, -100.0),
+            calculate_elo_range(10, 0.0)
+        );
+    }
+
+    #[test]
+    fn range_is_capped() {
+        assert_eq!(calculate_elo_range(20, 0.0), MAX_ELO_RANGE);
+        assert_eq!(calculate_elo_range(10, 10_000.0), MAX_ELO_RANGE);
+        assert_eq!(calculate_elo_range(1_000, 350.0), MAX_ELO_RANGE);
     }
 }
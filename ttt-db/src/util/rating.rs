@@ -0,0 +1,243 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use lazy_static::lazy_static;
+use skillratings::elo::{elo, EloConfig, EloRating};
+use skillratings::glicko2::{decay_deviation, glicko2, Glicko2Config, Glicko2Rating};
+use skillratings::Outcomes;
+use std::{env, str::FromStr};
+
+/// Length of a single Glicko-2 rating period.
+/// Deviation of inactive players grows once for every period without a game.
+const RATING_PERIOD_DAYS: i64 = 7;
+/// Deviation of a player with no rated games.
+pub(crate) const MAX_DEVIATION: f64 = 350.0;
+/// Number of games in a pool during which player's rating is provisional.
+pub const PROVISIONAL_GAMES: i64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RatingSystem {
+    Elo,
+    Glicko2,
+}
+
+impl RatingSystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Elo => "elo",
+            Self::Glicko2 => "glicko2",
+        }
+    }
+    /// Reads the rating system from the `RATING_SYSTEM` env variable, defaults to Elo.
+    pub fn from_env() -> Result<Self, String> {
+        match env::var("RATING_SYSTEM") {
+            Ok(s) => s.parse(),
+            Err(_) => Ok(Self::Elo),
+        }
+    }
+}
+
+impl FromStr for RatingSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "elo" => Ok(Self::Elo),
+            "glicko2" | "glicko-2" => Ok(Self::Glicko2),
+            _ => Err(format!("Unknown rating system: {}", s)),
+        }
+    }
+}
+
+lazy_static! {
+    /// Rating system used for every game, validated by the server on startup.
+    pub static ref RATING_SYSTEM: RatingSystem =
+        RatingSystem::from_env().expect("Invalid RATING_SYSTEM env variable!");
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+    pub games_played: i64,
+}
+
+/// Returns true while rating is still settling after the first games in a pool.
+pub fn is_provisional(games_played: i64) -> bool {
+    games_played < PROVISIONAL_GAMES
+}
+
+impl Rating {
+    pub fn is_provisional(&self) -> bool {
+        is_provisional(self.games_played)
+    }
+    /// K-factor used by Elo rating system.
+    /// New and low rated players move faster so they reach their real rating sooner,
+    /// while established high rated players are kept stable.
+    pub fn k_factor(&self) -> f64 {
+        if self.is_provisional() {
+            40.0
+        } else if self.rating < 1400.0 {
+            32.0
+        } else if self.rating < 2000.0 {
+            24.0
+        } else {
+            16.0
+        }
+    }
+    /// K-factor recorded with the game, if configured rating system uses one.
+    pub fn applied_k_factor(&self) -> Option<f64> {
+        match *RATING_SYSTEM {
+            RatingSystem::Elo => Some(self.k_factor()),
+            RatingSystem::Glicko2 => None,
+        }
+    }
+    /// Deviation used to widen matchmaking ranges.
+    /// Elo has no notion of uncertainty so it never widens the range.
+    pub fn matchmaking_deviation(&self) -> f64 {
+        match *RATING_SYSTEM {
+            RatingSystem::Elo => 0.0,
+            RatingSystem::Glicko2 => self.deviation,
+        }
+    }
+    /// Increases deviation for every rating period passed since the last game.
+    pub fn decay(self, last_game: Option<DateTime<FixedOffset>>) -> Self {
+        let last_game = match last_game {
+            Some(last_game) => last_game,
+            None => return self,
+        };
+        let periods = (Utc::now() - last_game.with_timezone(&Utc)).num_days() / RATING_PERIOD_DAYS;
+        let mut rating = Glicko2Rating::from(self);
+        for _ in 0..periods {
+            rating = decay_deviation(&rating);
+            if rating.deviation >= MAX_DEVIATION {
+                rating.deviation = MAX_DEVIATION;
+                break;
+            }
+        }
+        Self {
+            deviation: rating.deviation,
+            ..self
+        }
+    }
+}
+
+impl From<Rating> for Glicko2Rating {
+    fn from(r: Rating) -> Self {
+        Glicko2Rating {
+            rating: r.rating,
+            deviation: r.deviation,
+            volatility: r.volatility,
+        }
+    }
+}
+
+/// Calculates new ratings of both players with the configured rating system.
+/// `outcome` is from the perspective of the first player.
+pub(crate) fn rate(p1: Rating, p2: Rating, outcome: &Outcomes) -> (Rating, Rating) {
+    match *RATING_SYSTEM {
+        RatingSystem::Elo => {
+            // Each player can have a different K-factor so new ratings are calculated separately
+            let p1_elo = EloRating { rating: p1.rating };
+            let p2_elo = EloRating { rating: p2.rating };
+            let (p1_new, _) = elo(&p1_elo, &p2_elo, outcome, &EloConfig { k: p1.k_factor() });
+            let (_, p2_new) = elo(&p1_elo, &p2_elo, outcome, &EloConfig { k: p2.k_factor() });
+            (
+                Rating {
+                    rating: p1_new.rating,
+                    games_played: p1.games_played + 1,
+                    ..p1
+                },
+                Rating {
+                    rating: p2_new.rating,
+                    games_played: p2.games_played + 1,
+                    ..p2
+                },
+            )
+        }
+        RatingSystem::Glicko2 => {
+            let config = Glicko2Config::new();
+            let (p1_new, p2_new) = glicko2(&p1.into(), &p2.into(), outcome, &config);
+            (
+                Rating {
+                    rating: p1_new.rating,
+                    deviation: p1_new.deviation,
+                    volatility: p1_new.volatility,
+                    games_played: p1.games_played + 1,
+                },
+                Rating {
+                    rating: p2_new.rating,
+                    deviation: p2_new.deviation,
+                    volatility: p2_new.volatility,
+                    games_played: p2.games_played + 1,
+                },
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn rating(rating: f64, games_played: i64) -> Rating {
+        Rating {
+            rating,
+            deviation: 50.0,
+            volatility: 0.06,
+            games_played,
+        }
+    }
+
+    #[test]
+    fn provisional_until_enough_games() {
+        assert!(is_provisional(0));
+        assert!(is_provisional(PROVISIONAL_GAMES - 1));
+        assert!(!is_provisional(PROVISIONAL_GAMES));
+        assert!(rating(1500.0, 3).is_provisional());
+        assert!(!rating(1500.0, 30).is_provisional());
+    }
+
+    #[test]
+    fn k_factor_depends_on_games_and_rating() {
+        assert_eq!(rating(2500.0, 0).k_factor(), 40.0);
+        assert_eq!(rating(1200.0, 30).k_factor(), 32.0);
+        assert_eq!(rating(1400.0, 30).k_factor(), 24.0);
+        assert_eq!(rating(1999.0, 30).k_factor(), 24.0);
+        assert_eq!(rating(2000.0, 30).k_factor(), 16.0);
+    }
+
+    #[test]
+    fn elo_uses_k_factor_of_each_player() {
+        // Equal ratings, so the winner gains half of their K and the loser loses half of theirs
+        let (p1, p2) = rate(rating(1500.0, 0), rating(1500.0, 30), &Outcomes::WIN);
+        assert_eq!(*RATING_SYSTEM, RatingSystem::Elo);
+        assert!((p1.rating - 1520.0).abs() < 1e-9);
+        assert!((p2.rating - 1488.0).abs() < 1e-9);
+        assert_eq!(p1.games_played, 1);
+        assert_eq!(p2.games_played, 31);
+    }
+
+    #[test]
+    fn decay_without_games_keeps_deviation() {
+        assert_eq!(rating(1500.0, 30).decay(None).deviation, 50.0);
+        let recent = (Utc::now() - Duration::days(1)).into();
+        assert_eq!(rating(1500.0, 30).decay(Some(recent)).deviation, 50.0);
+    }
+
+    #[test]
+    fn decay_grows_deviation_up_to_max() {
+        let month_ago = (Utc::now() - Duration::days(30)).into();
+        let decayed = rating(1500.0, 30).decay(Some(month_ago));
+        assert!(decayed.deviation > 50.0);
+        assert!(decayed.deviation < MAX_DEVIATION);
+        let years_ago = (Utc::now() - Duration::days(3650)).into();
+        let uncertain = Rating {
+            deviation: MAX_DEVIATION - 0.1,
+            ..rating(1500.0, 30)
+        };
+        let decayed = uncertain.decay(Some(years_ago));
+        assert_eq!(decayed.deviation, MAX_DEVIATION);
+        assert_eq!(decayed.rating, 1500.0);
+    }
+}
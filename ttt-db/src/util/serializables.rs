@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 pub use crate::entity::prelude::*;
+use crate::util::rating::is_provisional;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UserMessage {
@@ -13,8 +14,27 @@ pub struct UserMessage {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UserDataMessage {
     pub username: String,
-    pub elo: i32,
+    /// Rating in the default pool
+    pub elo: i64,
     pub wins: i64,
     pub losses: i64,
     pub draws: i64,
+    pub ratings: Vec<PoolRatingMessage>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PoolRatingMessage {
+    #[serde(flatten)]
+    pub rating: UserRating,
+    pub provisional: bool,
+}
+
+impl From<UserRating> for PoolRatingMessage {
+    fn from(rating: UserRating) -> Self {
+        let provisional = is_provisional(rating.games_played);
+        Self {
+            rating,
+            provisional,
+        }
+    }
 }
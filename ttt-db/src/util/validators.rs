@@ -21,24 +21,52 @@ pub(crate) fn is_valid_password(password: &str) -> bool {
     password.len() >= 6 && !password.is_empty()
 }
 
+const SALT_LENGTH: usize = 16;
+
+/// Argon2 cost parameters, configurable so they can be raised over time.
+#[derive(Clone, Copy)]
+struct HashParams {
+    mem_cost: u32,
+    time_cost: u32,
+    lanes: u32,
+    hash_length: u32,
+}
+
+lazy_static! {
+    static ref HASH_PARAMS: HashParams = HashParams {
+        mem_cost: env_or("ARGON2_MEM_COST", 19456),
+        time_cost: env_or("ARGON2_TIME_COST", 2),
+        lanes: env_or("ARGON2_LANES", 1),
+        hash_length: env_or("ARGON2_HASH_LENGTH", 32),
+    };
+}
+
+fn env_or(key: &str, default: u32) -> u32 {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
 pub(crate) fn hash_password(password: &str) -> String {
     let password = password.as_bytes();
     let secret = env::var("PASSWORD_HASH_SECRET").expect("PASSWORD_HASH_SECRET not set!");
     let secret = secret.as_bytes();
-    let salt = b"thisissomesalt";
+    let salt: [u8; SALT_LENGTH] = rand::random();
+    let params = &*HASH_PARAMS;
     let config = argon2::Config {
         variant: argon2::Variant::Argon2id,
         version: argon2::Version::Version13,
-        mem_cost: 4096,
-        time_cost: 32,
-        lanes: 2,
+        mem_cost: params.mem_cost,
+        time_cost: params.time_cost,
+        lanes: params.lanes,
         thread_mode: argon2::ThreadMode::Parallel,
         secret,
         ad: &[],
-        hash_length: 16,
+        hash_length: params.hash_length,
     };
 
-    let hash = argon2::hash_encoded(password, salt, &config).expect("Error hashing password");
+    let hash = argon2::hash_encoded(password, &salt, &config).expect("Error hashing password");
 
     hash
 }
@@ -55,3 +83,94 @@ pub(crate) fn verify_password(password: &str, hash: &str) -> bool {
         Err(_) => false,
     }
 }
+
+/// Returns true if the hash uses the legacy salt or parameters other than the configured ones.
+pub(crate) fn needs_rehash(hash: &str) -> bool {
+    // Encoded hash looks like $argon2id$v=19$m=4096,t=32,p=2$<salt>$<hash>
+    let parts: Vec<&str> = hash.split('$').collect();
+    if parts.len() != 6 || parts[1] != "argon2id" || parts[2] != "v=19" {
+        return true;
+    }
+    let params = &*HASH_PARAMS;
+    let expected = format!(
+        "m={},t={},p={}",
+        params.mem_cost, params.time_cost, params.lanes
+    );
+    if parts[3] != expected {
+        return true;
+    }
+    // Salt and hash are base64 encoded without padding.
+    // Legacy salt is shorter than generated ones, so it is caught by the length check.
+    let salt_length = parts[4].len() * 3 / 4;
+    let hash_length = parts[5].len() * 3 / 4;
+    salt_length != SALT_LENGTH || hash_length != params.hash_length as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(params: &HashParams) -> argon2::Config<'_> {
+        argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            version: argon2::Version::Version13,
+            mem_cost: params.mem_cost,
+            time_cost: params.time_cost,
+            lanes: params.lanes,
+            thread_mode: argon2::ThreadMode::Parallel,
+            secret: &[],
+            ad: &[],
+            hash_length: params.hash_length,
+        }
+    }
+
+    #[test]
+    fn fresh_hash_is_up_to_date() {
+        env::set_var("PASSWORD_HASH_SECRET", "secret");
+        let hash = hash_password("password");
+        assert!(!needs_rehash(&hash));
+        assert!(verify_password("password", &hash));
+    }
+
+    #[test]
+    fn legacy_salt_needs_rehash() {
+        let hash =
+            argon2::hash_encoded(b"password", b"thisissomesalt", &config(&HASH_PARAMS)).unwrap();
+        assert!(needs_rehash(&hash));
+    }
+
+    #[test]
+    fn outdated_params_need_rehash() {
+        let salt: [u8; SALT_LENGTH] = rand::random();
+        let params = &*HASH_PARAMS;
+        let outdated = [
+            HashParams {
+                mem_cost: params.mem_cost / 2,
+                ..*params
+            },
+            HashParams {
+                time_cost: params.time_cost + 1,
+                ..*params
+            },
+            HashParams {
+                lanes: params.lanes + 1,
+                ..*params
+            },
+        ];
+        for params in &outdated {
+            let hash = argon2::hash_encoded(b"password", &salt, &config(params)).unwrap();
+            assert!(needs_rehash(&hash));
+        }
+    }
+
+    #[test]
+    fn other_variants_need_rehash() {
+        let salt: [u8; SALT_LENGTH] = rand::random();
+        let config = argon2::Config {
+            variant: argon2::Variant::Argon2i,
+            ..config(&HASH_PARAMS)
+        };
+        let hash = argon2::hash_encoded(b"password", &salt, &config).unwrap();
+        assert!(needs_rehash(&hash));
+    }
+}
@@ -11,6 +11,7 @@ pub(crate) enum ClientCommand {
     GetGameState,
     GetTimers,
     Resign,
+    Chat(String),
 }
 
 impl ClientCommand {
@@ -1,13 +1,18 @@
 use chrono::{DateTime, Utc};
+use ttt_db::{EndReason, Pool};
 use uuid::Uuid;
 
 pub struct CompletedGame {
     pub game_id: Uuid,
+    pub pool: Pool,
+    pub rated: bool,
     pub player1_id: i64,
     pub player1_elo: i64,
     pub player2_id: i64,
     pub player2_elo: i64,
     pub winner: Option<i64>,
+    pub moves: Vec<usize>,
+    pub end_reason: EndReason,
     pub game_start_time: DateTime<Utc>,
     pub game_end_time: DateTime<Utc>,
 }
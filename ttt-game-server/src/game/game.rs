@@ -1,22 +1,24 @@
 use actix::{
     fut::wrap_future, Actor, ActorContext, ActorFutureExt, Addr, AsyncContext, Context, Handler,
+    MessageResult,
 };
 use chrono::{DateTime, Utc};
 use log::info;
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use uuid::Uuid;
 
-use ttt_db::Match;
+use ttt_db::{EndReason, Match, Pool, TttDbConn, TttDbErr};
 
 use crate::{
     game::game_state::State,
     server::{messages::GameEnded, GameServer},
     timer::{GetTimer, PauseTimer, StartTimer, StopTimer, Timer},
-    ws::{GameWebsocket, ServerResponseMessage, ServerResponse},
+    ws::{message::StopConnection, GameWebsocket, ServerResponseMessage, ServerResponse},
 };
 
 use super::{
     completed_game::CompletedGame,
+    game_info::{Adjudication, GameInfo, GamePlayerInfo},
     game_state::{GameState, Timers},
     messages::*,
     ClientCommand,
@@ -25,21 +27,29 @@ use super::{
 #[derive(Debug)]
 pub struct Game {
     id: Uuid,
+    pool: Pool,
+    rated: bool,
     game_state: GameState,
     srv: Addr<GameServer>,
+    db: Arc<TttDbConn>,
     addrs: HashMap<i64, Addr<GameWebsocket>>,
     timers: HashMap<i64, Addr<Timer>>,
     started_at: DateTime<Utc>,
 }
 
 impl Game {
-    pub fn new(game: Match, srv: Addr<GameServer>) -> Self {
+    pub fn new(game: Match, srv: Addr<GameServer>, db: Arc<TttDbConn>) -> Self {
         let id = game.match_id;
-        let game_state = GameState::new(game.players);
+        let pool = game.pool;
+        let rated = game.rated;
+        let game_state = GameState::new(game.players, game.h2h);
         Self {
             id,
+            pool,
+            rated,
             game_state,
             srv,
+            db,
             addrs: HashMap::new(),
             timers: HashMap::new(),
             started_at: Utc::now(),
@@ -52,12 +62,13 @@ impl Actor for Game {
 
     fn started(&mut self, ctx: &mut Context<Self>) {
         info!("Game {} created", self.id);
+        let time = self.pool.time_control.seconds();
         let player_id = self.game_state.x_data.user_id;
         let first_turn = player_id;
-        let timer = Timer::new(player_id, 60.0, ctx.address().clone()).start();
+        let timer = Timer::new(player_id, time, ctx.address().clone()).start();
         self.timers.insert(player_id, timer);
         let player_id = self.game_state.o_data.user_id;
-        let timer = Timer::new(player_id, 60.0, ctx.address().clone()).start();
+        let timer = Timer::new(player_id, time, ctx.address().clone()).start();
         self.timers.insert(player_id, timer);
         ctx.run_later(Duration::from_secs(3), move |this, _| {
             this.game_state.state = State::Starting;
@@ -111,6 +122,18 @@ impl Handler<UserLeft> for Game {
     }
 }
 
+impl Handler<UserKicked> for Game {
+    type Result = ();
+
+    fn handle(&mut self, msg: UserKicked, _: &mut Self::Context) -> Self::Result {
+        let user_id = msg.0;
+        if let Some(addr) = self.addrs.remove(&user_id) {
+            addr.do_send(StopConnection);
+            info!("User {} kicked from game {}", user_id, self.id);
+        }
+    }
+}
+
 impl Handler<TimeExpired> for Game {
     type Result = ();
 
@@ -122,6 +145,7 @@ impl Handler<TimeExpired> for Game {
             self.game_state.x_data.user_id
         };
         self.game_state.winner = Some(winner_id);
+        self.game_state.end_reason = Some(EndReason::Timeout);
         ctx.notify(EndgameMessage {});
     }
 }
@@ -252,12 +276,109 @@ impl Handler<ClientCommandMessage> for Game {
                     self.game_state.x_data.user_id
                 };
                 self.game_state.winner = Some(winner_id);
+                self.game_state.end_reason = Some(EndReason::Resign);
                 ctx.notify(EndgameMessage {});
             }
+            ClientCommand::Chat(body) => {
+                let db = self.db.clone();
+                let game_id = self.id;
+                let chat = wrap_future::<_, Self>(async move {
+                    db.send_message(player_id, opp_id, Some(game_id), &body)
+                        .await
+                });
+                let chat = chat.map(move |res, this, _| {
+                    let player_addr = this.addrs.get(&player_id);
+                    match res {
+                        Ok(sent) => {
+                            if let Some(addr) = player_addr {
+                                addr.do_send(ServerResponseMessage(ServerResponse::Chat(
+                                    sent.message.clone(),
+                                )));
+                            }
+                            // Opponent muted the player
+                            if !sent.deliver {
+                                return;
+                            }
+                            if let Some(addr) = this.addrs.get(&opp_id) {
+                                addr.do_send(ServerResponseMessage(ServerResponse::Chat(
+                                    sent.message,
+                                )));
+                            }
+                        }
+                        Err(err) => {
+                            if let Some(addr) = player_addr {
+                                addr.do_send(ServerResponseMessage(ServerResponse::Error(
+                                    err.to_string(),
+                                )));
+                            }
+                        }
+                    }
+                });
+                ctx.spawn(chat);
+            }
         }
     }
 }
 
+impl Handler<GetGameInfo> for Game {
+    type Result = MessageResult<GetGameInfo>;
+
+    fn handle(&mut self, _: GetGameInfo, _: &mut Self::Context) -> Self::Result {
+        let players = [&self.game_state.x_data, &self.game_state.o_data]
+            .iter()
+            .map(|player| GamePlayerInfo {
+                user_id: player.user_id,
+                username: player.username.clone(),
+                elo: player.elo,
+                sign: player.sign,
+                connected: self.addrs.contains_key(&player.user_id),
+            })
+            .collect();
+        MessageResult(GameInfo {
+            game_id: self.id,
+            pool: self.pool,
+            rated: self.rated,
+            state: self.game_state.state,
+            players,
+            moves: self.game_state.moves.len(),
+            started_at: self.started_at,
+            elapsed: (Utc::now() - self.started_at).num_seconds(),
+        })
+    }
+}
+
+impl Handler<ForceEnd> for Game {
+    type Result = Result<(), TttDbErr>;
+
+    fn handle(&mut self, msg: ForceEnd, ctx: &mut Self::Context) -> Self::Result {
+        if self.game_state.state == State::Ended {
+            return Err(TttDbErr::InvalidInput("Game already ended.".to_string()));
+        }
+        let (winner, end_reason) = match msg.0 {
+            Adjudication::Abort => {
+                self.rated = false;
+                (None, EndReason::Aborted)
+            }
+            Adjudication::Draw => (None, EndReason::Adjudicated),
+            Adjudication::Win { winner } => {
+                if !self.game_state.p_map.contains_key(&winner) {
+                    return Err(TttDbErr::InvalidInput(
+                        "Winner is not a player in this game.".to_string(),
+                    ));
+                }
+                (Some(winner), EndReason::Adjudicated)
+            }
+        };
+        // Set right away so nothing else can end the game before the endgame message
+        self.game_state.state = State::Ended;
+        self.game_state.winner = winner;
+        self.game_state.end_reason = Some(end_reason);
+        ctx.notify(EndgameMessage {});
+        info!("Game {} force ended: {}", self.id, end_reason.as_str());
+        Ok(())
+    }
+}
+
 impl Handler<EndgameMessage> for Game {
     type Result = ();
 
@@ -284,11 +405,15 @@ impl Handler<EndgameMessage> for Game {
         }
         let game = CompletedGame {
             game_id: self.id,
+            pool: self.pool,
+            rated: self.rated,
             winner: self.game_state.winner,
             player1_id: self.game_state.x_data.user_id,
             player2_id: self.game_state.o_data.user_id,
             player1_elo: self.game_state.x_data.elo,
             player2_elo: self.game_state.o_data.elo,
+            moves: self.game_state.moves.clone(),
+            end_reason: self.game_state.end_reason.unwrap_or(EndReason::Draw),
             game_start_time: self.started_at,
             game_end_time: Utc::now(),
         };
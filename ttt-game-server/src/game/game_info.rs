@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ttt_db::Pool;
+use uuid::Uuid;
+
+use super::game_state::{Sign, State};
+
+/// Snapshot of an active game for admins.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameInfo {
+    pub game_id: Uuid,
+    pub pool: Pool,
+    pub rated: bool,
+    pub state: State,
+    pub players: Vec<GamePlayerInfo>,
+    /// Number of moves played so far
+    pub moves: usize,
+    pub started_at: DateTime<Utc>,
+    /// Seconds since the game was created
+    pub elapsed: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GamePlayerInfo {
+    pub user_id: i64,
+    pub username: String,
+    pub elo: i64,
+    pub sign: Sign,
+    /// Game connection of the player is open
+    pub connected: bool,
+}
+
+/// Result an admin ends a game with.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum Adjudication {
+    /// Game is recorded without a result and is not rated
+    Abort,
+    Draw,
+    Win {
+        winner: i64,
+    },
+}
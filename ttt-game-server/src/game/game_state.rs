@@ -2,7 +2,7 @@ use std::{collections::HashMap, rc::Rc};
 
 use serde::Serialize;
 
-use ttt_db::PlayerData;
+use ttt_db::{EndReason, HeadToHeadSummary, PlayerData};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum Sign {
@@ -26,6 +26,7 @@ pub struct Player {
     pub user_id: i64,
     pub username: String,
     pub elo: i64,
+    pub provisional: bool,
     pub sign: Sign,
 }
 
@@ -35,6 +36,7 @@ impl Player {
             user_id: player.user_id,
             username: player.username,
             elo: player.elo,
+            provisional: player.provisional,
             sign,
         }
     }
@@ -52,11 +54,20 @@ pub struct GameState {
     pub p_map: HashMap<i64, Rc<Player>>,
     #[serde(skip_serializing)]
     pub s_map: HashMap<Sign, Rc<Player>>,
+    #[serde(skip_serializing)]
+    pub h2h: HashMap<i64, HeadToHeadSummary>,
+    #[serde(skip_serializing)]
+    pub moves: Vec<usize>,
+    #[serde(skip_serializing)]
+    pub end_reason: Option<EndReason>,
 }
 
 impl GameState {
-    pub(crate) fn new(players: (PlayerData, PlayerData)) -> Self {
+    pub(crate) fn new(players: (PlayerData, PlayerData), h2h: HeadToHeadSummary) -> Self {
         let board = [None; 9];
+        let mut h2h_map = HashMap::new();
+        h2h_map.insert(players.0.user_id, h2h);
+        h2h_map.insert(players.1.user_id, h2h.flip());
         let first_turn = rand::random::<bool>();
         let x_data = Player::from_player_data(
             match first_turn {
@@ -91,6 +102,9 @@ impl GameState {
             o_data,
             p_map,
             s_map,
+            h2h: h2h_map,
+            moves: Vec::new(),
+            end_reason: None,
         }
     }
     pub fn to_msg(&self, user_id: i64) -> UserGameState {
@@ -114,13 +128,16 @@ impl GameState {
                 .collect::<Vec<Option<Sign>>>();
             if vec.iter().all(|sign| *sign == Some(Sign::X)) {
                 self.winner = Some(self.x_data.user_id);
+                self.end_reason = Some(EndReason::Line);
                 return true;
             } else if vec.iter().all(|sign| *sign == Some(Sign::O)) {
                 self.winner = Some(self.o_data.user_id);
+                self.end_reason = Some(EndReason::Line);
                 return true;
             }
         }
         if self.board.iter().all(|x| x.is_some()) {
+            self.end_reason = Some(EndReason::Draw);
             return true;
         }
         false
@@ -131,6 +148,7 @@ impl GameState {
         }
         let sign = self.p_map.get(&user_id).unwrap().sign;
         self.board[i] = Some(sign);
+        self.moves.push(i);
         return true;
     }
 }
@@ -160,6 +178,7 @@ pub struct UserGameState {
     pub turn_player: UserPlayer,
     pub your_data: Player,
     pub opp_data: Player,
+    pub h2h: HeadToHeadSummary,
 }
 
 impl UserGameState {
@@ -192,6 +211,7 @@ impl UserGameState {
         } else {
             Winner::Draw
         };
+        let h2h = state.h2h.get(&user_id).copied().unwrap_or_default();
         let state = state.state;
         let your_data = (&*your_data).clone();
         let opp_data = (&*opp_data).clone();
@@ -202,6 +222,7 @@ impl UserGameState {
             turn_player,
             your_data,
             opp_data,
+            h2h,
         }
     }
     pub fn serialize(&self) -> String {
@@ -1,7 +1,9 @@
 use actix::{Addr, Message};
+use ttt_db::TttDbErr;
 
 use crate::ws::GameWebsocket;
 
+use super::game_info::{Adjudication, GameInfo};
 use super::ClientCommand;
 
 #[derive(Message)]
@@ -12,6 +14,10 @@ pub struct UserJoined(pub i64, pub Addr<GameWebsocket>);
 #[rtype(result = "()")]
 pub struct UserLeft(pub i64);
 
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct UserKicked(pub i64);
+
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct TimeExpired(pub i64);
@@ -23,3 +29,12 @@ pub(crate) struct ClientCommandMessage(pub i64, pub ClientCommand);
 #[derive(Message)]
 #[rtype(result = "()")]
 pub(crate) struct EndgameMessage;
+
+#[derive(Message)]
+#[rtype(result = "GameInfo")]
+pub struct GetGameInfo;
+
+/// Ends the game with the result decided by an admin.
+#[derive(Message)]
+#[rtype(result = "Result<(), TttDbErr>")]
+pub struct ForceEnd(pub Adjudication);
@@ -1,6 +1,7 @@
 pub(crate) mod command;
 pub mod completed_game;
 pub mod game;
+pub mod game_info;
 pub mod game_state;
 pub mod messages;
 
@@ -3,7 +3,7 @@ use uuid::Uuid;
 
 use ttt_db::Match;
 
-use crate::game::{completed_game::CompletedGame, Game};
+use crate::game::{completed_game::CompletedGame, game_info::GameInfo, Game};
 
 #[derive(Message)]
 #[rtype(result = "()")]
@@ -16,3 +16,13 @@ pub struct GetGameAddress(pub Uuid);
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct GameEnded(pub CompletedGame);
+
+/// Closes game connections of a user in every active game.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct KickUser(pub i64);
+
+/// Returns snapshots of all active games.
+#[derive(Message)]
+#[rtype(result = "Vec<GameInfo>")]
+pub struct GetActiveGames;
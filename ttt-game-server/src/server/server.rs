@@ -1,11 +1,17 @@
-use actix::{fut::wrap_future, Actor, ActorFutureExt, Addr, AsyncContext, Context, Handler};
+use actix::{
+    fut::wrap_future, Actor, ActorFutureExt, Addr, AsyncContext, Context, Handler, ResponseFuture,
+};
 use log::{info, warn};
 use std::{collections::HashMap, sync::Arc};
 use uuid::Uuid;
 
-use ttt_db::TttDbConn;
+use ttt_db::{GameRecord, TttDbConn};
 
-use crate::game::Game;
+use crate::game::{
+    game_info::GameInfo,
+    messages::{GetGameInfo, UserKicked},
+    Game,
+};
 
 use super::messages::*;
 
@@ -48,7 +54,7 @@ impl Handler<CreateNewGame> for GameServer {
         let create_active = create_active.map(|_, _, _| ());
         ctx.spawn(create_active);
         let game_id = m.match_id;
-        let game = Game::new(m, ctx.address()).start();
+        let game = Game::new(m, ctx.address(), self.db.clone()).start();
         self.games.insert(game_id, game);
     }
 }
@@ -72,14 +78,18 @@ impl Handler<GameEnded> for GameServer {
         self.games.remove(&game.game_id);
         let db = self.db.clone();
         let fut = wrap_future::<_, Self>(async move {
-            db.record_game(
-                game.game_id,
-                game.player1_id,
-                game.player2_id,
-                game.winner,
-                game.game_start_time.into(),
-                game.game_end_time.into(),
-            )
+            db.record_game(GameRecord {
+                game_id: game.game_id,
+                pool: game.pool,
+                rated: game.rated,
+                user1_id: game.player1_id,
+                user2_id: game.player2_id,
+                winner: game.winner,
+                moves: game.moves,
+                end_reason: game.end_reason,
+                start_time: game.game_start_time.into(),
+                end_time: game.game_end_time.into(),
+            })
             .await
         });
         let fut = fut.map(|res, _, _| {
@@ -90,3 +100,30 @@ impl Handler<GameEnded> for GameServer {
         ctx.spawn(fut);
     }
 }
+
+impl Handler<KickUser> for GameServer {
+    type Result = ();
+    fn handle(&mut self, msg: KickUser, _: &mut Self::Context) -> Self::Result {
+        for game in self.games.values() {
+            game.do_send(UserKicked(msg.0));
+        }
+    }
+}
+
+impl Handler<GetActiveGames> for GameServer {
+    type Result = ResponseFuture<Vec<GameInfo>>;
+    fn handle(&mut self, _: GetActiveGames, _: &mut Self::Context) -> Self::Result {
+        let games: Vec<Addr<Game>> = self.games.values().cloned().collect();
+        Box::pin(async move {
+            let mut infos = Vec::new();
+            for game in games {
+                // Game could have ended in the meantime
+                if let Ok(info) = game.send(GetGameInfo).await {
+                    infos.push(info);
+                }
+            }
+            infos.sort_unstable_by_key(|i| i.started_at);
+            infos
+        })
+    }
+}
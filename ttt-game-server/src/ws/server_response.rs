@@ -1,5 +1,6 @@
 use actix::Message;
 use serde::Serialize;
+use ttt_db::ChatMessage;
 
 use crate::game::game_state::{State, Timers, UserGameState, UserPlayer};
 
@@ -16,6 +17,7 @@ pub(crate) enum ServerResponse {
     GameResult(String),
     GameState(UserGameState),
     Time(Timers),
+    Chat(ChatMessage),
     Error(String),
 }
 
@@ -8,8 +8,10 @@ use actix::{ActorContext, Addr};
 use actix::{AsyncContext, Handler};
 use actix_web_actors::ws::Message::Text;
 use actix_web_actors::ws::{self, CloseCode, CloseReason};
-use log::info;
+use log::{info, warn};
 use std::time::{Duration, Instant};
+use ttt_db::{Connection, Presence};
+use uuid::Uuid;
 
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
@@ -17,17 +19,35 @@ const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
 pub struct GameWebsocket {
     hb: Instant,
     user_id: i64,
+    game_id: Uuid,
     game: Addr<Game>,
+    presence: Presence,
 }
 
 impl GameWebsocket {
-    pub fn new(user_id: i64, game: Addr<Game>) -> Self {
+    pub fn new(user_id: i64, game_id: Uuid, game: Addr<Game>, presence: Presence) -> Self {
         Self {
             hb: Instant::now(),
             user_id,
+            game_id,
             game,
+            presence,
         }
     }
+    fn update_presence(&self, connected: bool) {
+        let presence = self.presence.clone();
+        let user_id = self.user_id;
+        let conn = Connection::Game(self.game_id);
+        actix::spawn(async move {
+            let res = match connected {
+                true => presence.connect(user_id, conn).await,
+                false => presence.disconnect(user_id, conn).await,
+            };
+            if let Err(err) = res {
+                warn!("Error updating presence of user {}: {:?}", user_id, err);
+            }
+        });
+    }
 }
 
 impl Actor for GameWebsocket {
@@ -35,11 +55,13 @@ impl Actor for GameWebsocket {
 
     fn started(&mut self, ctx: &mut Self::Context) {
         self.game.do_send(UserJoined(self.user_id, ctx.address()));
+        self.update_presence(true);
         self.hb(ctx);
     }
 
     fn stopping(&mut self, _: &mut Self::Context) -> Running {
         self.game.do_send(UserLeft(self.user_id));
+        self.update_presence(false);
         Running::Stop
     }
 }
@@ -52,7 +74,7 @@ impl GameWebsocket {
                 ctx.stop();
                 return;
             }
-
+            act.update_presence(true);
             ctx.ping(b"hi");
         });
     }
@@ -0,0 +1,5 @@
+pub mod server;
+pub mod ws;
+
+pub use server::LobbyServer;
+pub use ws::{LobbyEvent, LobbyWebsocket, MatchmakingStatus};
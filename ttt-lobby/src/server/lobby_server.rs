@@ -0,0 +1,127 @@
+use actix::{fut::wrap_future, Actor, ActorFutureExt, Addr, AsyncContext, Context, Handler};
+use log::{info, warn};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use ttt_db::TttDbConn;
+
+use crate::ws::{CloseConnection, LobbyEvent, LobbyEventMessage, LobbyWebsocket};
+
+use super::messages::*;
+
+/// How often expired chat messages are deleted.
+const MESSAGE_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Keeps track of lobby connections and routes events to them.
+/// A user can be connected from multiple clients at once.
+#[derive(Debug, Clone)]
+pub struct LobbyServer {
+    db: Arc<TttDbConn>,
+    users: HashMap<i64, HashMap<usize, Addr<LobbyWebsocket>>>,
+    next_id: usize,
+}
+
+impl LobbyServer {
+    pub fn new(db: Arc<TttDbConn>) -> Self {
+        Self {
+            db,
+            users: HashMap::new(),
+            next_id: 0,
+        }
+    }
+    fn send(&self, user_id: i64, event: LobbyEvent) {
+        if let Some(conns) = self.users.get(&user_id) {
+            for addr in conns.values() {
+                addr.do_send(LobbyEventMessage(event.clone()));
+            }
+        }
+    }
+}
+
+impl Actor for LobbyServer {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Context<Self>) {
+        info!("Lobby server is alive");
+        ctx.run_interval(MESSAGE_PURGE_INTERVAL, |this, ctx| {
+            let db = this.db.clone();
+            let purge = wrap_future::<_, Self>(async move { db.purge_expired_messages().await });
+            let purge = purge.map(|res, _this, _ctx| match res {
+                Ok(n) => info!("Purged {} expired messages", n),
+                Err(err) => warn!("Error purging messages: {:?}!", err),
+            });
+            ctx.spawn(purge);
+        });
+    }
+
+    fn stopped(&mut self, _ctx: &mut Context<Self>) {
+        info!("Lobby server stopped");
+    }
+}
+
+impl Handler<Connect> for LobbyServer {
+    type Result = usize;
+    fn handle(&mut self, msg: Connect, ctx: &mut Self::Context) -> Self::Result {
+        let (user_id, addr) = (msg.0, msg.1);
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.users
+            .entry(user_id)
+            .or_default()
+            .insert(id, addr.clone());
+        // Remind user of the game they left
+        let db = self.db.clone();
+        let active_game =
+            wrap_future::<_, Self>(async move { db.get_user_active_game(user_id).await });
+        let active_game = active_game.map(move |res, _this, _ctx| match res {
+            Ok(Some(game_id)) => {
+                addr.do_send(LobbyEventMessage(LobbyEvent::ActiveGame(game_id)));
+            }
+            Ok(None) => (),
+            Err(err) => warn!("Lobby error: {:?}!", err),
+        });
+        ctx.spawn(active_game);
+        id
+    }
+}
+
+impl Handler<Disconnect> for LobbyServer {
+    type Result = ();
+    fn handle(&mut self, msg: Disconnect, _: &mut Self::Context) -> Self::Result {
+        let (user_id, id) = (msg.0, msg.1);
+        if let Some(conns) = self.users.get_mut(&user_id) {
+            conns.remove(&id);
+            if conns.is_empty() {
+                self.users.remove(&user_id);
+            }
+        }
+    }
+}
+
+impl Handler<Notify> for LobbyServer {
+    type Result = ();
+    fn handle(&mut self, msg: Notify, _: &mut Self::Context) -> Self::Result {
+        self.send(msg.0, msg.1);
+    }
+}
+
+impl Handler<Broadcast> for LobbyServer {
+    type Result = ();
+    fn handle(&mut self, msg: Broadcast, _: &mut Self::Context) -> Self::Result {
+        for user_id in self.users.keys() {
+            self.send(*user_id, msg.0.clone());
+        }
+    }
+}
+
+impl Handler<Kick> for LobbyServer {
+    type Result = ();
+    fn handle(&mut self, msg: Kick, _: &mut Self::Context) -> Self::Result {
+        let (user_id, event) = (msg.0, msg.1);
+        if let Some(conns) = self.users.remove(&user_id) {
+            for addr in conns.values() {
+                addr.do_send(LobbyEventMessage(event.clone()));
+                addr.do_send(CloseConnection);
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use actix::{Addr, Message};
+
+use crate::ws::{LobbyEvent, LobbyWebsocket};
+
+/// Registers lobby connection of a user, returns connection id.
+#[derive(Message)]
+#[rtype(result = "usize")]
+pub(crate) struct Connect(pub i64, pub Addr<LobbyWebsocket>);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub(crate) struct Disconnect(pub i64, pub usize);
+
+/// Sends event to every lobby connection of a user.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Notify(pub i64, pub LobbyEvent);
+
+/// Sends event to every connected user.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Broadcast(pub LobbyEvent);
+
+/// Sends event to every lobby connection of a user and closes them.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Kick(pub i64, pub LobbyEvent);
@@ -0,0 +1,4 @@
+pub mod lobby_server;
+pub mod messages;
+
+pub use lobby_server::LobbyServer;
@@ -0,0 +1,42 @@
+use actix::Message;
+use serde::Serialize;
+use ttt_db::{Ban, ChatMessage, Pool};
+use uuid::Uuid;
+
+/// Events pushed to clients over the lobby websocket.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "cmd", content = "msg")]
+#[serde(rename_all = "snake_case")]
+pub enum LobbyEvent {
+    /// Username of the user who sent the friend request
+    FriendRequest(String),
+    /// Username of the user who accepted the friend request
+    FriendRequestAccepted(String),
+    Announcement(String),
+    DirectMessage(ChatMessage),
+    Matchmaking(MatchmakingStatus),
+    /// User has a game in progress
+    ActiveGame(Uuid),
+    /// User has been banned, connection is closed right after
+    Banned(Ban),
+    /// User deleted their account, connection is closed right after
+    AccountDeleted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status")]
+#[serde(rename_all = "snake_case")]
+pub enum MatchmakingStatus {
+    Queued { pool: Pool },
+    Left,
+    MatchFound { match_id: Uuid },
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub(crate) struct LobbyEventMessage(pub LobbyEvent);
+
+/// Closes the lobby connection.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub(crate) struct CloseConnection;
@@ -0,0 +1,6 @@
+pub mod lobby_event;
+pub mod ws;
+
+pub(crate) use lobby_event::{CloseConnection, LobbyEventMessage};
+pub use lobby_event::{LobbyEvent, MatchmakingStatus};
+pub use ws::LobbyWebsocket;
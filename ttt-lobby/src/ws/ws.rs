@@ -0,0 +1,134 @@
+use actix::{fut, Actor, ActorFutureExt, ContextFutureSpawner, Running, StreamHandler, WrapFuture};
+use actix::{ActorContext, Addr};
+use actix::{AsyncContext, Handler};
+use actix_web_actors::ws;
+use log::{debug, warn};
+use std::time::{Duration, Instant};
+use ttt_db::{Connection, Presence};
+
+use super::{CloseConnection, LobbyEventMessage};
+use crate::server::messages::{Connect, Disconnect};
+use crate::LobbyServer;
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct LobbyWebsocket {
+    hb: Instant,
+    id: Option<usize>,
+    user_id: i64,
+    lobby: Addr<LobbyServer>,
+    presence: Presence,
+}
+
+impl LobbyWebsocket {
+    pub fn new(user_id: i64, lobby: Addr<LobbyServer>, presence: Presence) -> Self {
+        Self {
+            hb: Instant::now(),
+            id: None,
+            user_id,
+            lobby,
+            presence,
+        }
+    }
+    fn update_presence(&self, connected: bool) {
+        let presence = self.presence.clone();
+        let user_id = self.user_id;
+        actix::spawn(async move {
+            let res = match connected {
+                true => presence.connect(user_id, Connection::Lobby).await,
+                false => presence.disconnect(user_id, Connection::Lobby).await,
+            };
+            if let Err(err) = res {
+                warn!("Error updating presence of user {}: {:?}", user_id, err);
+            }
+        });
+    }
+}
+
+impl Actor for LobbyWebsocket {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.lobby
+            .send(Connect(self.user_id, ctx.address()))
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                match res {
+                    Ok(id) => act.id = Some(id),
+                    Err(_) => ctx.stop(),
+                }
+                fut::ready(())
+            })
+            .wait(ctx);
+        self.update_presence(true);
+        self.hb(ctx);
+    }
+
+    fn stopping(&mut self, _: &mut Self::Context) -> Running {
+        if let Some(id) = self.id {
+            self.lobby.do_send(Disconnect(self.user_id, id));
+        }
+        self.update_presence(false);
+        Running::Stop
+    }
+}
+
+impl LobbyWebsocket {
+    fn hb(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
+            if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
+                debug!("Disconnecting failed heartbeat");
+                ctx.stop();
+                return;
+            }
+            act.update_presence(true);
+            ctx.ping(b"hi");
+        });
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for LobbyWebsocket {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Ping(msg)) => {
+                self.hb = Instant::now();
+                ctx.pong(&msg);
+            }
+            Ok(ws::Message::Pong(_)) => {
+                self.hb = Instant::now();
+            }
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(ws::Message::Continuation(_)) => {
+                ctx.stop();
+            }
+            // Lobby only pushes events to clients
+            Ok(_) => (),
+            Err(e) => {
+                warn!("Lobby websocket protocol error: {}", e);
+                ctx.stop();
+            }
+        }
+    }
+}
+
+impl Handler<LobbyEventMessage> for LobbyWebsocket {
+    type Result = ();
+
+    fn handle(&mut self, msg: LobbyEventMessage, ctx: &mut Self::Context) {
+        let text = serde_json::to_string(&msg.0).unwrap();
+        ctx.text(text);
+    }
+}
+
+impl Handler<CloseConnection> for LobbyWebsocket {
+    type Result = ();
+
+    fn handle(&mut self, _: CloseConnection, ctx: &mut Self::Context) {
+        ctx.close(None);
+        ctx.stop();
+    }
+}
@@ -1,4 +1,4 @@
 mod worker;
 
 pub use worker::mail_worker::MailWorker;
-pub use worker::messages::SendVerificationEmail;
+pub use worker::messages::{SendEmailChangedNotice, SendPasswordResetEmail, SendVerificationEmail};
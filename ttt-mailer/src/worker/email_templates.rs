@@ -48,3 +48,93 @@ pub(crate) fn verification_email_stdout(
         verification_email_raw(username, uuid)
     )
 }
+
+pub(crate) fn password_reset_email(
+    username: &str,
+    email: &str,
+    sender: &str,
+    uuid: &Uuid,
+) -> lettre::Message {
+    lettre::Message::builder()
+        .to(format!("{} <{}>", username, email).parse().unwrap())
+        .from(format!("Tic Tac Toe <{}>", sender).parse().unwrap())
+        .subject("Password reset")
+        .body(password_reset_email_raw(username, uuid))
+        .unwrap()
+}
+
+pub(crate) fn password_reset_email_raw(username: &str, uuid: &Uuid) -> String {
+    let pt1 = format!("Hi {},\n", username);
+    let pt2 = "We received a request to reset the password of your Tic Tac Toe account.\n";
+    let pt3 = format!(
+        "Use the following code to choose a new password:\n{}\n",
+        uuid
+    );
+    let pt4 = "The code expires in an hour and can only be used once.\n";
+    let pt5 = "If you didn't request a password reset, you can ignore this email.";
+    pt1 + pt2 + &pt3 + pt4 + pt5
+}
+
+pub(crate) fn password_reset_email_stdout(
+    username: &str,
+    email: &str,
+    sender: &str,
+    uuid: &Uuid,
+) -> String {
+    format!(
+        "
+    From: Tic Tac Toe <{}>
+    To: {} <{}>
+    Subject: Password reset\n
+        {}
+    ",
+        sender,
+        username,
+        email,
+        password_reset_email_raw(username, uuid)
+    )
+}
+
+pub(crate) fn email_changed_email(
+    username: &str,
+    email: &str,
+    sender: &str,
+    new_email: &str,
+) -> lettre::Message {
+    lettre::Message::builder()
+        .to(format!("{} <{}>", username, email).parse().unwrap())
+        .from(format!("Tic Tac Toe <{}>", sender).parse().unwrap())
+        .subject("Email changed")
+        .body(email_changed_email_raw(username, new_email))
+        .unwrap()
+}
+
+pub(crate) fn email_changed_email_raw(username: &str, new_email: &str) -> String {
+    let pt1 = format!("Hi {},\n", username);
+    let pt2 = format!(
+        "The email address of your Tic Tac Toe account was changed to {}.\n",
+        new_email
+    );
+    let pt3 = "If you didn't make this change, please contact us right away.";
+    pt1 + &pt2 + pt3
+}
+
+pub(crate) fn email_changed_email_stdout(
+    username: &str,
+    email: &str,
+    sender: &str,
+    new_email: &str,
+) -> String {
+    format!(
+        "
+    From: Tic Tac Toe <{}>
+    To: {} <{}>
+    Subject: Email changed\n
+        {}
+    ",
+        sender,
+        username,
+        email,
+        email_changed_email_raw(username, new_email)
+    )
+}
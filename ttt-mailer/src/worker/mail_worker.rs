@@ -77,6 +77,53 @@ impl Handler<SendVerificationEmail> for MailWorker {
     }
 }
 
+impl Handler<SendPasswordResetEmail> for MailWorker {
+    type Result = ();
+    fn handle(&mut self, msg: SendPasswordResetEmail, ctx: &mut Self::Context) -> Self::Result {
+        let mailer = self.mailer.clone();
+        let sender = self.username.clone();
+        let (username, email, uuid) = (msg.username, msg.email, msg.uuid);
+        let fut = wrap_future::<_, Self>(async move {
+            let stdout = password_reset_email_stdout(&username, &email, &sender, &uuid);
+            let msg = password_reset_email(&username, &email, &sender, &uuid);
+            send_email(mailer, msg, stdout).await
+        });
+        ctx.spawn(fut);
+    }
+}
+
+impl Handler<SendEmailChangedNotice> for MailWorker {
+    type Result = ();
+    fn handle(&mut self, msg: SendEmailChangedNotice, ctx: &mut Self::Context) -> Self::Result {
+        let mailer = self.mailer.clone();
+        let sender = self.username.clone();
+        let (username, old_email, new_email) = (msg.username, msg.old_email, msg.new_email);
+        let fut = wrap_future::<_, Self>(async move {
+            let stdout = email_changed_email_stdout(&username, &old_email, &sender, &new_email);
+            let msg = email_changed_email(&username, &old_email, &sender, &new_email);
+            send_email(mailer, msg, stdout).await
+        });
+        ctx.spawn(fut);
+    }
+}
+
+/// Sends the message, or prints it to stdout if mailer is not set.
+async fn send_email(
+    mailer: Arc<Option<AsyncSmtpTransport<Tokio1Executor>>>,
+    msg: lettre::Message,
+    stdout: String,
+) -> () {
+    match &*mailer {
+        None => println!("{}", stdout),
+        Some(mailer) => {
+            let res = mailer.send(msg).await;
+            if let Err(err) = res {
+                warn!("Error sending email: {:?}", err)
+            }
+        }
+    }
+}
+
 pub async fn send_verification_email(
     mailer: Arc<Option<AsyncSmtpTransport<Tokio1Executor>>>,
     sender: String,
@@ -18,3 +18,40 @@ impl SendVerificationEmail {
         }
     }
 }
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SendPasswordResetEmail {
+    pub username: String,
+    pub email: String,
+    pub uuid: Uuid,
+}
+
+impl SendPasswordResetEmail {
+    pub fn new(username: String, email: String, uuid: Uuid) -> Self {
+        Self {
+            username,
+            email,
+            uuid,
+        }
+    }
+}
+
+/// Notice sent to the old address after the email of an account is changed.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SendEmailChangedNotice {
+    pub username: String,
+    pub old_email: String,
+    pub new_email: String,
+}
+
+impl SendEmailChangedNotice {
+    pub fn new(username: String, old_email: String, new_email: String) -> Self {
+        Self {
+            username,
+            old_email,
+            new_email,
+        }
+    }
+}
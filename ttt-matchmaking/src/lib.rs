@@ -2,3 +2,4 @@ mod worker;
 pub mod ws;
 
 pub use worker::matchmaking_worker::MatchmakingWorker;
+pub use worker::messages::{GetConnectedUsers, KickUser};
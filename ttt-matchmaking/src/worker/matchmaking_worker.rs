@@ -1,12 +1,17 @@
-use actix::{fut::wrap_future, Actor, ActorFutureExt, Addr, AsyncContext, Context, Handler};
+use actix::{
+    fut::wrap_future, Actor, ActorFutureExt, Addr, AsyncContext, Context, Handler, MessageResult,
+};
 use log::{error, info, warn};
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use ttt_game_server::server::messages::CreateNewGame;
 use ttt_game_server::server::GameServer;
+use ttt_lobby::server::messages::Notify;
+use ttt_lobby::{LobbyEvent, LobbyServer, MatchmakingStatus};
 
 use ttt_db::{Match, TttDbConn};
 
-use crate::ws::{message::MatchMessage, MatchmakingWebsocket};
+use crate::ws::message::{Kicked, MatchMessage};
+use crate::ws::MatchmakingWebsocket;
 
 use super::messages::*;
 
@@ -15,25 +20,39 @@ pub struct MatchmakingWorker {
     db: Arc<TttDbConn>,
     active_users: HashMap<i64, Addr<MatchmakingWebsocket>>,
     game_server: Addr<GameServer>,
+    lobby: Addr<LobbyServer>,
 }
 
 impl MatchmakingWorker {
-    pub fn new(db: Arc<TttDbConn>, game_server: Addr<GameServer>) -> Self {
+    pub fn new(
+        db: Arc<TttDbConn>,
+        game_server: Addr<GameServer>,
+        lobby: Addr<LobbyServer>,
+    ) -> Self {
         Self {
             db,
             active_users: HashMap::new(),
             game_server,
+            lobby,
         }
     }
-    fn new_match(&self, new_match: Match) {
+    fn notify_status(&self, user_id: i64, status: MatchmakingStatus) {
+        let event = LobbyEvent::Matchmaking(status);
+        self.lobby.do_send(Notify(user_id, event));
+    }
+    fn new_match(&mut self, new_match: Match) {
         let uuid = new_match.match_id.clone();
         let msg = MatchMessage { msg: "Match found".to_string(), match_id: uuid };
         let user_ids = (new_match.players.0.user_id, new_match.players.1.user_id);
         self.game_server.do_send(CreateNewGame(new_match));
+        // Users are removed here so leaving the queue after the match is found is not reported
         let addrs = (
-            self.active_users.get(&user_ids.0),
-            self.active_users.get(&user_ids.1),
+            self.active_users.remove(&user_ids.0),
+            self.active_users.remove(&user_ids.1),
         );
+        for user_id in [user_ids.0, user_ids.1] {
+            self.notify_status(user_id, MatchmakingStatus::MatchFound { match_id: uuid });
+        }
         if let Some(addr) = addrs.0 {
             addr.do_send(msg.clone());
         }
@@ -80,13 +99,15 @@ impl Handler<FindMatches> for MatchmakingWorker {
 impl Handler<AddUserToQueue> for MatchmakingWorker {
     type Result = ();
     fn handle(&mut self, msg: AddUserToQueue, ctx: &mut Self::Context) -> Self::Result {
-        let (user_id, addr) = (msg.0, msg.1);
+        let (user_id, pool, addr) = (msg.0, msg.1, msg.2);
         self.active_users.insert(user_id, addr);
         let db = self.db.clone();
         let add_user =
-            wrap_future::<_, Self>(async move { db.insert_user_into_mm_queue(user_id).await });
-        let add_user = add_user.map(|res, _this, _ctx| match res {
-            Ok(_) => (),
+            wrap_future::<_, Self>(
+                async move { db.insert_user_into_mm_queue(user_id, pool).await },
+            );
+        let add_user = add_user.map(move |res, this, _ctx| match res {
+            Ok(_) => this.notify_status(user_id, MatchmakingStatus::Queued { pool }),
             Err(err) => error!("Matchmaking error: {:?}!", err),
         });
         ctx.spawn(add_user);
@@ -98,7 +119,9 @@ impl Handler<RemoveUserFromQueue> for MatchmakingWorker {
     type Result = ();
     fn handle(&mut self, msg: RemoveUserFromQueue, ctx: &mut Self::Context) -> Self::Result {
         let user_id = msg.0;
-        self.active_users.remove(&user_id);
+        if self.active_users.remove(&user_id).is_some() {
+            self.notify_status(user_id, MatchmakingStatus::Left);
+        }
         let db = self.db.clone();
         let remove_user =
             wrap_future::<_, Self>(async move { db.remove_user_from_mm_queue(user_id).await });
@@ -109,3 +132,29 @@ impl Handler<RemoveUserFromQueue> for MatchmakingWorker {
         ctx.spawn(remove_user);
     }
 }
+
+impl Handler<KickUser> for MatchmakingWorker {
+    type Result = ();
+    fn handle(&mut self, msg: KickUser, ctx: &mut Self::Context) -> Self::Result {
+        let user_id = msg.0;
+        if let Some(addr) = self.active_users.remove(&user_id) {
+            addr.do_send(Kicked);
+            self.notify_status(user_id, MatchmakingStatus::Left);
+        }
+        let db = self.db.clone();
+        let remove_user =
+            wrap_future::<_, Self>(async move { db.remove_user_from_mm_queue(user_id).await });
+        let remove_user = remove_user.map(|res, _this, _ctx| match res {
+            Ok(_) => (),
+            Err(err) => warn!("Matchmaking error: {:?}!", err),
+        });
+        ctx.spawn(remove_user);
+    }
+}
+
+impl Handler<GetConnectedUsers> for MatchmakingWorker {
+    type Result = MessageResult<GetConnectedUsers>;
+    fn handle(&mut self, _: GetConnectedUsers, _: &mut Self::Context) -> Self::Result {
+        MessageResult(self.active_users.keys().cloned().collect())
+    }
+}
@@ -1,4 +1,5 @@
 use actix::{Addr, Message};
+use ttt_db::Pool;
 
 use crate::ws::ws::MatchmakingWebsocket;
 
@@ -8,8 +9,18 @@ pub(crate) struct FindMatches;
 
 #[derive(Message, Debug)]
 #[rtype(result = "()")]
-pub(crate) struct AddUserToQueue(pub i64, pub Addr<MatchmakingWebsocket>);
+pub(crate) struct AddUserToQueue(pub i64, pub Pool, pub Addr<MatchmakingWebsocket>);
 
 #[derive(Message, Debug)]
 #[rtype(result = "()")]
 pub(crate) struct RemoveUserFromQueue(pub i64);
+
+/// Removes user from the queue and closes their matchmaking connection.
+#[derive(Message, Debug)]
+#[rtype(result = "()")]
+pub struct KickUser(pub i64);
+
+/// Returns ids of users with an open matchmaking connection.
+#[derive(Message, Debug)]
+#[rtype(result = "Vec<i64>")]
+pub struct GetConnectedUsers;
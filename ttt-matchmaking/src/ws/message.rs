@@ -12,3 +12,8 @@ pub(crate) struct MatchMessage {
     pub msg: String,
     pub match_id: Uuid,
 }
+
+/// Sent to the connection of a user removed from the queue by the server.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub(crate) struct Kicked;
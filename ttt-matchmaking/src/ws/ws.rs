@@ -1,7 +1,7 @@
 use crate::MatchmakingWorker;
-use log::{debug, info};
+use log::{debug, info, warn};
 
-use super::message::{AlreadyQueued, MatchMessage};
+use super::message::{AlreadyQueued, Kicked, MatchMessage};
 use crate::worker::messages::{AddUserToQueue, RemoveUserFromQueue};
 use actix::{Actor, Running, StreamHandler};
 use actix::{ActorContext, Addr};
@@ -9,6 +9,7 @@ use actix::{AsyncContext, Handler};
 use actix_web_actors::ws::Message::Text;
 use actix_web_actors::ws::{self, CloseCode, CloseReason};
 use std::time::{Duration, Instant};
+use ttt_db::{Connection, Pool, Presence};
 
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
@@ -16,32 +17,60 @@ const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
 pub struct MatchmakingWebsocket {
     hb: Instant,
     user_id: i64,
+    pool: Pool,
     mm_worker: Addr<MatchmakingWorker>,
+    presence: Presence,
 }
 
 impl MatchmakingWebsocket {
-    pub fn new(user_id: i64, mm_worker: Addr<MatchmakingWorker>) -> Self {
+    pub fn new(
+        user_id: i64,
+        pool: Pool,
+        mm_worker: Addr<MatchmakingWorker>,
+        presence: Presence,
+    ) -> Self {
         Self {
             hb: Instant::now(),
             user_id,
+            pool,
             mm_worker,
+            presence,
         }
     }
+    fn update_presence(&self, connected: bool) {
+        let presence = self.presence.clone();
+        let user_id = self.user_id;
+        let conn = Connection::Matchmaking(self.pool);
+        actix::spawn(async move {
+            let res = match connected {
+                true => presence.connect(user_id, conn).await,
+                false => presence.disconnect(user_id, conn).await,
+            };
+            if let Err(err) = res {
+                warn!("Error updating presence of user {}: {:?}", user_id, err);
+            }
+        });
+    }
 }
 
 impl Actor for MatchmakingWebsocket {
     type Context = ws::WebsocketContext<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
-        info!("User {} entered matchmaking", self.user_id);
-        let msg = AddUserToQueue(self.user_id, ctx.address());
+        info!(
+            "User {} entered matchmaking for {}",
+            self.user_id, self.pool
+        );
+        let msg = AddUserToQueue(self.user_id, self.pool, ctx.address());
         self.mm_worker.do_send(msg);
+        self.update_presence(true);
         self.hb(ctx);
     }
 
     fn stopping(&mut self, _: &mut Self::Context) -> Running {
         let msg = RemoveUserFromQueue(self.user_id);
         self.mm_worker.do_send(msg);
+        self.update_presence(false);
         Running::Stop
     }
 }
@@ -54,7 +83,7 @@ impl MatchmakingWebsocket {
                 ctx.stop();
                 return;
             }
-
+            act.update_presence(true);
             ctx.ping(b"hi");
         });
     }
@@ -111,3 +140,12 @@ impl Handler<MatchMessage> for MatchmakingWebsocket {
         });
     }
 }
+
+impl Handler<Kicked> for MatchmakingWebsocket {
+    type Result = ();
+
+    fn handle(&mut self, _: Kicked, ctx: &mut Self::Context) {
+        ctx.close(Some(CloseReason::from(CloseCode::Policy)));
+        ctx.stop();
+    }
+}
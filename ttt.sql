@@ -1,3 +1,37 @@
+-- public.audit_log definition
+
+-- Drop table
+
+-- DROP TABLE public.audit_log;
+
+CREATE TABLE public.audit_log (
+	id bigserial NOT NULL,
+	admin_id int8 NOT NULL,
+	"action" varchar NOT NULL,
+	target_user_id int8 NULL,
+	target_game_id uuid NULL,
+	target_report_id int8 NULL,
+	before_value varchar NULL,
+	after_value varchar NULL,
+	reason varchar NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT audit_log_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX audit_log_admin_idx ON public.audit_log (admin_id, id);
+CREATE INDEX audit_log_target_user_idx ON public.audit_log (target_user_id, id);
+
+-- Audit log is append-only
+CREATE FUNCTION public.audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+	RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only BEFORE UPDATE OR DELETE ON public.audit_log
+	FOR EACH ROW EXECUTE FUNCTION public.audit_log_append_only();
+
+
 -- public.email_verification definition
 
 -- Drop table
@@ -14,6 +48,24 @@ CREATE TABLE public.email_verification (
 );
 
 
+-- public.friendships definition
+
+-- Drop table
+
+-- DROP TABLE public.friendships;
+
+CREATE TABLE public.friendships (
+	requester_id int8 NOT NULL,
+	addressee_id int8 NOT NULL,
+	accepted bool NOT NULL DEFAULT false,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	accepted_on timestamptz NULL,
+	CONSTRAINT friendships_pk PRIMARY KEY (requester_id, addressee_id)
+);
+
+CREATE INDEX friendships_addressee_idx ON public.friendships (addressee_id);
+
+
 -- public.games definition
 
 -- Drop table
@@ -27,11 +79,199 @@ CREATE TABLE public.games (
 	end_time timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
 	start_time timestamptz NOT NULL,
 	game_id uuid NOT NULL,
+	moves varchar NOT NULL DEFAULT '',
+	end_reason varchar NULL,
 	user1_elo int8 NOT NULL DEFAULT 0,
 	user2_elo int8 NOT NULL DEFAULT 0,
+	user1_elo_after int8 NOT NULL DEFAULT 0,
+	user2_elo_after int8 NOT NULL DEFAULT 0,
+	user1_elo_delta int8 NOT NULL DEFAULT 0,
+	user2_elo_delta int8 NOT NULL DEFAULT 0,
+	variant varchar NOT NULL DEFAULT 'classic',
+	time_control varchar NOT NULL DEFAULT 'blitz',
+	rated bool NOT NULL DEFAULT true,
+	rating_system varchar NOT NULL DEFAULT 'elo',
+	user1_deviation float8 NOT NULL DEFAULT 350,
+	user2_deviation float8 NOT NULL DEFAULT 350,
+	user1_k_factor float8 NULL,
+	user2_k_factor float8 NULL,
+	user1_provisional bool NOT NULL DEFAULT false,
+	user2_provisional bool NOT NULL DEFAULT false,
 	CONSTRAINT games_pk PRIMARY KEY (game_id)
 );
 
+CREATE INDEX games_user1_history_idx ON public.games (user1_id, end_time DESC, game_id DESC);
+CREATE INDEX games_user2_history_idx ON public.games (user2_id, end_time DESC, game_id DESC);
+
+
+-- public.messages definition
+
+-- Drop table
+
+-- DROP TABLE public.messages;
+
+CREATE TABLE public.messages (
+	id bigserial NOT NULL,
+	sender_id int8 NOT NULL,
+	recipient_id int8 NOT NULL,
+	game_id uuid NULL,
+	body varchar NOT NULL,
+	sent_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	reported_by int8 NULL,
+	reported_at timestamptz NULL,
+	removed bool NOT NULL DEFAULT false,
+	CONSTRAINT messages_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX messages_conversation_idx ON public.messages (LEAST(sender_id, recipient_id), GREATEST(sender_id, recipient_id), id DESC);
+CREATE INDEX messages_reported_idx ON public.messages (reported_at) WHERE reported_by IS NOT NULL;
+
+
+-- public.password_reset definition
+
+-- Drop table
+
+-- DROP TABLE public.password_reset;
+
+CREATE TABLE public.password_reset (
+	id uuid NOT NULL,
+	user_id int8 NOT NULL,
+	time_generated timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	expires_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0) + '01:00:00'::interval,
+	CONSTRAINT password_reset_pk PRIMARY KEY (id),
+	CONSTRAINT password_reset_unique UNIQUE (user_id)
+);
+
+
+-- public.rating_history definition
+
+-- Drop table
+
+-- DROP TABLE public.rating_history;
+
+CREATE TABLE public.rating_history (
+	id bigserial NOT NULL,
+	user_id int8 NOT NULL,
+	game_id uuid NOT NULL,
+	variant varchar NOT NULL,
+	time_control varchar NOT NULL,
+	elo_before int8 NOT NULL,
+	elo_after int8 NOT NULL,
+	recorded_at timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT rating_history_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX rating_history_user_idx ON public.rating_history (user_id, variant, time_control, recorded_at);
+
+
+-- public.recovery_codes definition
+
+-- Drop table
+
+-- DROP TABLE public.recovery_codes;
+
+CREATE TABLE public.recovery_codes (
+	id bigserial NOT NULL,
+	user_id int8 NOT NULL,
+	code_hash varchar NOT NULL,
+	used_on timestamptz NULL,
+	CONSTRAINT recovery_codes_pk PRIMARY KEY (id)
+);
+CREATE INDEX recovery_codes_user_idx ON public.recovery_codes (user_id);
+
+
+-- public.reports definition
+
+-- Drop table
+
+-- DROP TABLE public.reports;
+
+CREATE TABLE public.reports (
+	id bigserial NOT NULL,
+	reporter_id int8 NOT NULL,
+	reported_id int8 NOT NULL,
+	game_id uuid NULL,
+	category varchar NOT NULL,
+	description varchar NOT NULL DEFAULT '',
+	status varchar NOT NULL DEFAULT 'open',
+	assignee_id int8 NULL,
+	resolution varchar NULL,
+	banned bool NOT NULL DEFAULT false,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	resolved_on timestamptz NULL,
+	CONSTRAINT reports_pk PRIMARY KEY (id)
+);
+
+CREATE INDEX reports_status_idx ON public.reports (status, created_on);
+
+
+-- public.user_blocks definition
+
+-- Drop table
+
+-- DROP TABLE public.user_blocks;
+
+CREATE TABLE public.user_blocks (
+	user_id int8 NOT NULL,
+	blocked_id int8 NOT NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT user_blocks_pk PRIMARY KEY (user_id, blocked_id)
+);
+
+CREATE INDEX user_blocks_blocked_idx ON public.user_blocks (blocked_id);
+
+
+-- public.user_identities definition
+
+-- Drop table
+
+-- DROP TABLE public.user_identities;
+
+CREATE TABLE public.user_identities (
+	provider varchar NOT NULL,
+	subject varchar NOT NULL,
+	user_id int8 NOT NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT user_identities_pk PRIMARY KEY (provider, subject),
+	CONSTRAINT user_identities_unique_user UNIQUE (user_id, provider)
+);
+
+
+-- public.user_mutes definition
+
+-- Drop table
+
+-- DROP TABLE public.user_mutes;
+
+CREATE TABLE public.user_mutes (
+	user_id int8 NOT NULL,
+	muted_id int8 NOT NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT user_mutes_pk PRIMARY KEY (user_id, muted_id)
+);
+
+
+-- public.user_ratings definition
+
+-- Drop table
+
+-- DROP TABLE public.user_ratings;
+
+CREATE TABLE public.user_ratings (
+	user_id int8 NOT NULL,
+	variant varchar NOT NULL,
+	time_control varchar NOT NULL,
+	wins int8 NOT NULL DEFAULT 0,
+	losses int8 NOT NULL DEFAULT 0,
+	draws int8 NOT NULL DEFAULT 0,
+	games_played int8 NOT NULL DEFAULT 0,
+	elo int8 NOT NULL DEFAULT 1200,
+	deviation float8 NOT NULL DEFAULT 350,
+	volatility float8 NOT NULL DEFAULT 0.06,
+	last_game timestamptz NULL,
+	CONSTRAINT user_ratings_pk PRIMARY KEY (user_id, variant, time_control)
+);
+
 
 -- public.user_stats definition
 
@@ -44,11 +284,26 @@ CREATE TABLE public.user_stats (
 	wins int8 NOT NULL DEFAULT 0,
 	losses int8 NOT NULL DEFAULT 0,
 	draws int8 NOT NULL DEFAULT 0,
-	elo int8 NOT NULL DEFAULT 1200,
 	CONSTRAINT user_stats_pk PRIMARY KEY (user_id)
 );
 
 
+-- public.user_totp definition
+
+-- Drop table
+
+-- DROP TABLE public.user_totp;
+
+CREATE TABLE public.user_totp (
+	user_id int8 NOT NULL,
+	secret bytea NOT NULL,
+	last_step int8 NULL,
+	confirmed_on timestamptz NULL,
+	created_on timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP(0),
+	CONSTRAINT user_totp_pk PRIMARY KEY (user_id)
+);
+
+
 -- public.users definition
 
 -- Drop table
@@ -64,8 +319,10 @@ CREATE TABLE public.users (
 	email_verified bool NOT NULL DEFAULT false,
 	is_banned bool NOT NULL DEFAULT false,
 	ban_ends timestamptz NULL,
+	ban_reason varchar NULL,
 	user_id bigserial NOT NULL,
 	guest bool NOT NULL DEFAULT false,
+	deleted_on timestamptz NULL,
 	CONSTRAINT users_pkey PRIMARY KEY (user_id),
 	CONSTRAINT users_unique_email UNIQUE (email),
 	CONSTRAINT users_unique_username UNIQUE (username)